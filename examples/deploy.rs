@@ -10,8 +10,6 @@ use cw_orch::{
     daemon::{networks, TxSender},
     prelude::*,
 };
-use dotenv;
-use pretty_env_logger;
 
 const FEE_COLLECTION_ADDR: &str = "juno1rec44j9xq8aj4w5kun796f89njzvdlezwk7cy4";
 
@@ -28,7 +26,17 @@ pub fn main() -> anyhow::Result<()> {
     contract.upload_if_needed()?;
 
     if contract.address().is_err() {
-        contract.instantiate(&InstantiateMsg {}, Some(&sender), None)?;
+        contract.instantiate(
+            &InstantiateMsg {
+                config: Config {
+                    distributor: None,
+                    keeper_fee_pct: None,
+                    tokenfactory: None,
+                },
+            },
+            Some(&sender),
+            None,
+        )?;
 
         let _ = chain.commit_any::<Any>(
             vec![juno_feeshare_msg(
@@ -43,7 +51,11 @@ pub fn main() -> anyhow::Result<()> {
     }
 
     // can call any necessary execution messages here like adding admin, etc.
-    contract.set_config(Config {})?;
+    contract.set_config(Config {
+        distributor: None,
+        keeper_fee_pct: None,
+        tokenfactory: None,
+    })?;
 
     // can also query any necessary data here from the contract
     contract.config()?;
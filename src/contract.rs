@@ -1,10 +1,32 @@
 use crate::error::ContractError;
-use crate::execute::{set_config::exec_set_config, Context};
-use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
-use crate::query::{config::query_config, ReadonlyContext};
+use crate::execute::{
+    campaign::{exec_close_campaign, exec_create_campaign, exec_update_campaign},
+    claim::exec_claim,
+    distribute::exec_distribute,
+    hooks::{exec_add_hook, exec_remove_hook, reply_callback, reply_hook, CALLBACK_REPLY_ID, HOOK_REPLY_ID},
+    ibc::{reply_ibc_transfer, sudo_ibc_lifecycle, IBC_TRANSFER_REPLY_IDS},
+    payout::{reply_payout, PAYOUT_REPLY_IDS},
+    recover::{exec_finalize_expired, exec_recover},
+    set_config::exec_set_config,
+    upsert::{exec_upsert_cw1155, exec_upsert_cw20, exec_upsert_native, exec_upsert_nft, exec_upsert_shares_native},
+    Context,
+};
+use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, SudoMsg};
+use crate::query::{
+    campaign_claims::query_claims_by_campaign,
+    campaigns::{query_campaign, query_campaigns, query_token_sources, query_token_totals},
+    claim::{query_claim, query_claim_by_key, query_claims_by_ids},
+    claims::query_claims,
+    config::query_config,
+    history::{query_claim_history, query_claimed_totals},
+    hooks::query_hooks,
+    simulate::query_simulate_claim,
+    summary::query_claimable_summary,
+    ReadonlyContext,
+};
 use crate::state;
 use cosmwasm_std::{entry_point, to_json_binary};
-use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Response};
+use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response};
 use cw2::set_contract_version;
 
 const CONTRACT_NAME: &str = "crates.io:cw-claims";
@@ -18,7 +40,7 @@ pub fn instantiate(
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
-    Ok(state::init(Context { deps, env, info }, msg)?)
+    state::init(Context { deps, env, info }, msg)
 }
 
 #[entry_point]
@@ -31,6 +53,20 @@ pub fn execute(
     let ctx = Context { deps, env, info };
     match msg {
         ExecuteMsg::SetConfig(config) => exec_set_config(ctx, config),
+        ExecuteMsg::Upsert(msg) => exec_upsert_native(ctx, msg),
+        ExecuteMsg::UpsertShares(msg) => exec_upsert_shares_native(ctx, msg),
+        ExecuteMsg::Receive(msg) => exec_upsert_cw20(ctx, msg),
+        ExecuteMsg::ReceiveNft(msg) => exec_upsert_nft(ctx, msg),
+        ExecuteMsg::BatchReceive(msg) => exec_upsert_cw1155(ctx, msg),
+        ExecuteMsg::Claim(msg) => exec_claim(ctx, msg),
+        ExecuteMsg::Distribute { name, limit } => exec_distribute(ctx, name, limit),
+        ExecuteMsg::CreateCampaign(msg) => exec_create_campaign(ctx, msg),
+        ExecuteMsg::UpdateCampaign(msg) => exec_update_campaign(ctx, msg),
+        ExecuteMsg::CloseCampaign { name } => exec_close_campaign(ctx, name),
+        ExecuteMsg::Recover { name, limit } => exec_recover(ctx, name, limit),
+        ExecuteMsg::FinalizeExpired { name, limit } => exec_finalize_expired(ctx, name, limit),
+        ExecuteMsg::AddHook { address, name } => exec_add_hook(ctx, address, name),
+        ExecuteMsg::RemoveHook { address, name } => exec_remove_hook(ctx, address, name),
     }
 }
 
@@ -43,10 +79,63 @@ pub fn query(
     let ctx = ReadonlyContext { deps, env };
     let result = match msg {
         QueryMsg::Config {} => to_json_binary(&query_config(ctx)?),
+        QueryMsg::Campaign { name } => to_json_binary(&query_campaign(ctx, name)?),
+        QueryMsg::Campaigns { cursor } => to_json_binary(&query_campaigns(ctx, cursor)?),
+        QueryMsg::ClaimHistory { scope, cursor } => to_json_binary(&query_claim_history(ctx, scope, cursor)?),
+        QueryMsg::ClaimedTotals { address } => to_json_binary(&query_claimed_totals(ctx, address)?),
+        QueryMsg::ClaimableSummary { address } => to_json_binary(&query_claimable_summary(ctx, address)?),
+        QueryMsg::Claims(msg) => to_json_binary(&query_claims(ctx, msg)?),
+        QueryMsg::Claim { id } => to_json_binary(&query_claim(ctx, id)?),
+        QueryMsg::ClaimByKey { name, token, recipient } => {
+            to_json_binary(&query_claim_by_key(ctx, name, token, recipient)?)
+        },
+        QueryMsg::ClaimsByIds { ids } => to_json_binary(&query_claims_by_ids(ctx, ids)?),
+        QueryMsg::ClaimsByCampaign(msg) => to_json_binary(&query_claims_by_campaign(ctx, msg)?),
+        QueryMsg::Hooks { name } => to_json_binary(&query_hooks(ctx, name)?),
+        QueryMsg::TokenSources { name } => to_json_binary(&query_token_sources(ctx, name)?),
+        QueryMsg::TokenTotals { name, cursor } => to_json_binary(&query_token_totals(ctx, name, cursor)?),
+        QueryMsg::SimulateClaim {
+            address,
+            ids,
+            recipient,
+            filter,
+            cursor,
+            amount,
+        } => to_json_binary(&query_simulate_claim(
+            ctx, address, ids, recipient, filter, cursor, amount,
+        )?),
     }?;
     Ok(result)
 }
 
+#[entry_point]
+pub fn reply(
+    deps: DepsMut,
+    env: Env,
+    reply: Reply,
+) -> Result<Response, ContractError> {
+    match reply.id {
+        HOOK_REPLY_ID => reply_hook(reply),
+        CALLBACK_REPLY_ID => reply_callback(reply),
+        id if IBC_TRANSFER_REPLY_IDS.contains(&id) => reply_ibc_transfer(deps, reply),
+        id if PAYOUT_REPLY_IDS.contains(&id) => reply_payout(deps, env, reply),
+        id => Err(ContractError::ValidationError {
+            reason: format!("unknown reply id {}", id),
+        }),
+    }
+}
+
+#[entry_point]
+pub fn sudo(
+    deps: DepsMut,
+    env: Env,
+    msg: SudoMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        SudoMsg::IbcLifecycleComplete(msg) => sudo_ibc_lifecycle(deps, env, msg),
+    }
+}
+
 #[entry_point]
 pub fn migrate(
     deps: DepsMut,
@@ -0,0 +1,43 @@
+//! Subset of the cw1155 interface used to receive, send, mint and burn
+//! multi-tokens.
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Binary, Uint128};
+
+/// Message sent by a cw1155 contract to this contract on `BatchSendFrom`.
+#[cw_serde]
+pub struct Cw1155BatchReceiveMsg {
+    pub operator: String,
+    pub from: Option<String>,
+    pub batch: Vec<(String, Uint128)>,
+    pub msg: Binary,
+}
+
+#[cw_serde]
+pub enum Cw1155ExecuteMsg {
+    BatchSendFrom {
+        from: String,
+        to: String,
+        batch: Vec<(String, Uint128)>,
+        msg: Option<Binary>,
+    },
+    Mint {
+        to: String,
+        token_id: String,
+        value: Uint128,
+        msg: Option<Binary>,
+    },
+    BatchBurn {
+        from: String,
+        batch: Vec<(String, Uint128)>,
+    },
+}
+
+#[cw_serde]
+pub enum Cw1155QueryMsg {
+    Balance { owner: String, token_id: String },
+}
+
+#[cw_serde]
+pub struct BalanceResponse {
+    pub balance: Uint128,
+}
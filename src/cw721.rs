@@ -0,0 +1,32 @@
+//! Subset of the cw721 interface used to receive, transfer and burn NFTs.
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::Binary;
+use serde::Deserialize;
+
+/// Message sent by a cw721 collection to this contract on `SendNft`.
+#[cw_serde]
+pub struct Cw721ReceiveMsg {
+    pub sender: String,
+    pub token_id: String,
+    pub msg: Binary,
+}
+
+#[cw_serde]
+pub enum Cw721ExecuteMsg {
+    TransferNft { recipient: String, token_id: String },
+    Burn { token_id: String },
+}
+
+#[cw_serde]
+pub enum Cw721QueryMsg {
+    OwnerOf {
+        token_id: String,
+        include_expired: Option<bool>,
+    },
+}
+
+/// Only the owner is read, so other fields in the response are ignored.
+#[derive(Deserialize)]
+pub struct OwnerOfResponse {
+    pub owner: String,
+}
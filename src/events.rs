@@ -0,0 +1,52 @@
+//! Events emitted for each claim record affected by a transaction.
+//!
+//! Indexers can rebuild the full set of pending claims from these events alone.
+//! The chain prefixes each event type with `wasm-`. All events share the same
+//! attributes:
+//!
+//! | attribute   | description                                         |
+//! |-------------|-----------------------------------------------------|
+//! | `id`        | claim record ID                                     |
+//! | `recipient` | address the claim belongs to                        |
+//! | `name`      | campaign name                                       |
+//! | `token`     | token key, `d:<denom>`, `a:<cw20 address>`,         |
+//! |             | `n:<cw721 collection>:<token id>` or                |
+//! |             | `m:<cw1155 contract>:<token id>`                    |
+//! | `delta`     | amount by which the record's balance changed        |
+//! | `balance`   | record's balance after the change (`0` if deleted)  |
+//!
+//! Event types:
+//!
+//! - `claim_created`: a new record was upserted.
+//! - `claim_increased`: an existing record was upserted.
+//! - `claim_paid`: `delta` was paid out of the record, via claim or distribution.
+//! - `claim_revoked`: the record was recovered by its campaign's creator.
+//! - `claim_restored`: `delta` was returned to the record after the transfer
+//!   paying it out failed, or timed out over IBC. Failed keeper fees and
+//!   refunds are restored as records of the keeper or refund recipient.
+use cosmwasm_std::{attr, Event, Uint128};
+
+use crate::models::ClaimRecord;
+
+pub const CLAIM_CREATED: &str = "claim_created";
+pub const CLAIM_INCREASED: &str = "claim_increased";
+pub const CLAIM_PAID: &str = "claim_paid";
+pub const CLAIM_REVOKED: &str = "claim_revoked";
+pub const CLAIM_RESTORED: &str = "claim_restored";
+
+/// Build an event of the given type for a change to a claim record.
+pub fn claim_event(
+    ty: &str,
+    claim: &ClaimRecord,
+    delta: Uint128,
+    balance: Uint128,
+) -> Event {
+    Event::new(ty).add_attributes(vec![
+        attr("id", claim.id.to_string()),
+        attr("recipient", claim.recipient.to_string()),
+        attr("name", claim.name.to_owned()),
+        attr("token", claim.token.to_key()),
+        attr("delta", delta.to_string()),
+        attr("balance", balance.to_string()),
+    ])
+}
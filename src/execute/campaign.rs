@@ -0,0 +1,574 @@
+use crate::{
+    error::ContractError,
+    math::{add_u128, add_u32, sub_u128, sub_u32},
+    models::{Campaign, CampaignStatus, CampaignTokenTotals, ExpiryAction, RemoteSender},
+    msg::{CreateCampaignMsg, UpdateCampaignMsg},
+    state::{CAMPAIGNS, CAMPAIGN_TOKEN_SOURCES, CAMPAIGN_TOKEN_TOTALS, CONFIG},
+    token::{Token, TokenFactory, TokenSource},
+};
+use cosmwasm_std::{attr, Addr, Api, Env, Order, Response, Storage, Timestamp, Uint128};
+
+use super::{ibc::validate_remote_sender, Context};
+
+pub const MAX_NAME_LEN: usize = 64;
+pub const MAX_TITLE_LEN: usize = 128;
+pub const MAX_DESCRIPTION_LEN: usize = 1024;
+pub const MAX_URL_LEN: usize = 512;
+pub const MAX_TOKEN_SOURCES: usize = 20;
+
+pub fn exec_create_campaign(
+    ctx: Context,
+    msg: CreateCampaignMsg,
+) -> Result<Response, ContractError> {
+    let Context { deps, env, info } = ctx;
+    let CreateCampaignMsg {
+        name,
+        title,
+        description,
+        image_url,
+        claim_start,
+        claim_end,
+        tokens,
+        mint,
+        expiry_action,
+        remote_depositor,
+    } = msg;
+
+    validate_text("name", &name, MAX_NAME_LEN)?;
+    validate_text("title", &title, MAX_TITLE_LEN)?;
+    if let Some(description) = &description {
+        validate_text("description", description, MAX_DESCRIPTION_LEN)?;
+    }
+    if let Some(image_url) = &image_url {
+        validate_text("image_url", image_url, MAX_URL_LEN)?;
+    }
+    if let (Some(start), Some(end)) = (claim_start, claim_end) {
+        if start >= end {
+            return Err(ContractError::ValidationError {
+                reason: "claim_start must be earlier than claim_end".to_owned(),
+            });
+        }
+    }
+    if let Some(end) = claim_end {
+        if end <= env.block.time {
+            return Err(ContractError::ValidationError {
+                reason: "claim_end must be in the future".to_owned(),
+            });
+        }
+    }
+
+    if let Some(mint) = &mint {
+        if mint.cap.is_zero() {
+            return Err(ContractError::ValidationError {
+                reason: "mint cap must be greater than zero".to_owned(),
+            });
+        }
+        if let Token::Nft { .. } = mint.token {
+            return Err(ContractError::ValidationError {
+                reason: "NFTs cannot be minted on claim".to_owned(),
+            });
+        }
+        if mint.token.get_denom().is_some() && CONFIG.load(deps.storage)?.tokenfactory.is_none() {
+            return Err(ContractError::ValidationError {
+                reason: "minting native denoms requires a tokenfactory module".to_owned(),
+            });
+        }
+    }
+
+    let expiry_action = match expiry_action {
+        Some(ExpiryAction::SendTo(address)) => ExpiryAction::SendTo(deps.api.addr_validate(address.as_str())?),
+        Some(action) => action,
+        None => ExpiryAction::Refund,
+    };
+    if expiry_action == ExpiryAction::Burn {
+        for source in tokens.iter() {
+            ensure_burnable(deps.storage, &env.contract.address, source)?;
+        }
+    }
+
+    if let Some(remote) = &remote_depositor {
+        validate_remote_depositor(remote)?;
+    }
+
+    let mint_source = mint.as_ref().map(|m| m.token.source());
+
+    if CAMPAIGNS.has(deps.storage, &name) {
+        return Err(ContractError::ValidationError {
+            reason: format!("campaign {} already exists", name),
+        });
+    }
+
+    CAMPAIGNS.save(
+        deps.storage,
+        &name,
+        &Campaign {
+            name: name.to_owned(),
+            creator: info.sender.to_owned(),
+            title,
+            description,
+            image_url,
+            created_at: env.block.time,
+            updated_at: env.block.time,
+            status: CampaignStatus::Open,
+            n_outstanding_tokens: 0,
+            claim_start,
+            claim_end,
+            callback: None,
+            remote_depositor,
+            mint,
+            expiry_action,
+        },
+    )?;
+
+    for source in tokens.iter().chain(mint_source.iter()) {
+        allow_token_source(deps.storage, deps.api, &name, source)?;
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "create_campaign"),
+        attr("name", name),
+        attr("creator", info.sender),
+    ]))
+}
+
+pub fn exec_update_campaign(
+    ctx: Context,
+    msg: UpdateCampaignMsg,
+) -> Result<Response, ContractError> {
+    let Context { deps, env, info } = ctx;
+    let UpdateCampaignMsg {
+        name,
+        title,
+        description,
+        image_url,
+        claim_end,
+        add_tokens,
+        remote_depositor,
+    } = msg;
+
+    let mut campaign = load_campaign_for_creator(deps.storage, &name, &info.sender)?;
+
+    if let Some(title) = title {
+        validate_text("title", &title, MAX_TITLE_LEN)?;
+        campaign.title = title;
+    }
+    if let Some(description) = description {
+        validate_text("description", &description, MAX_DESCRIPTION_LEN)?;
+        campaign.description = Some(description);
+    }
+    if let Some(image_url) = image_url {
+        validate_text("image_url", &image_url, MAX_URL_LEN)?;
+        campaign.image_url = Some(image_url);
+    }
+    if let Some(claim_end) = claim_end {
+        // Only allow the window to be extended, so that recipients can't have
+        // it pulled out from under them.
+        if campaign.claim_end.map(|t| claim_end < t).unwrap_or(true) {
+            return Err(ContractError::ValidationError {
+                reason: "claim_end can only be extended".to_owned(),
+            });
+        }
+        campaign.claim_end = Some(claim_end);
+    }
+    if let Some(sources) = add_tokens {
+        for source in sources.iter() {
+            if campaign.expiry_action == ExpiryAction::Burn {
+                ensure_burnable(deps.storage, &env.contract.address, source)?;
+            }
+            allow_token_source(deps.storage, deps.api, &name, source)?;
+        }
+    }
+    if let Some(remote) = remote_depositor {
+        validate_remote_depositor(&remote)?;
+        campaign.remote_depositor = Some(remote);
+    }
+
+    campaign.updated_at = env.block.time;
+    CAMPAIGNS.save(deps.storage, &name, &campaign)?;
+
+    Ok(Response::new().add_attributes(vec![attr("action", "update_campaign"), attr("name", name)]))
+}
+
+pub fn exec_close_campaign(
+    ctx: Context,
+    name: String,
+) -> Result<Response, ContractError> {
+    let Context { deps, env, info } = ctx;
+
+    let mut campaign = load_campaign_for_creator(deps.storage, &name, &info.sender)?;
+
+    if campaign.status != CampaignStatus::Open {
+        return Err(ContractError::ValidationError {
+            reason: format!("campaign {} is not open", name),
+        });
+    }
+
+    campaign.status = if campaign.n_outstanding_tokens == 0 {
+        CampaignStatus::Finalized
+    } else {
+        CampaignStatus::Closed
+    };
+    campaign.updated_at = env.block.time;
+    CAMPAIGNS.save(deps.storage, &name, &campaign)?;
+
+    Ok(Response::new().add_attributes(vec![attr("action", "close_campaign"), attr("name", name)]))
+}
+
+/// Load a campaign, ensuring that it's open and that the depositor created it
+/// or, for upserts arriving through IBC hooks, that the already validated
+/// remote sender is the campaign's remote depositor.
+pub fn load_open_campaign_for_depositor(
+    store: &dyn Storage,
+    name: &String,
+    depositor: &Addr,
+    remote_sender: Option<&RemoteSender>,
+) -> Result<Campaign, ContractError> {
+    let campaign = if let Some(remote_sender) = remote_sender {
+        let campaign = load_campaign(store, name)?;
+        if campaign.remote_depositor.as_ref() != Some(remote_sender) {
+            return Err(ContractError::NotAuthorized {
+                reason: "remote sender is not the campaign's remote depositor".to_owned(),
+            });
+        }
+        campaign
+    } else {
+        load_campaign_for_creator(store, name, depositor)?
+    };
+    if campaign.status != CampaignStatus::Open {
+        return Err(ContractError::ValidationError {
+            reason: format!("campaign {} is not open", name),
+        });
+    }
+    Ok(campaign)
+}
+
+/// Accept deposits of tokens from the given denom or contract in a campaign.
+fn allow_token_source(
+    store: &mut dyn Storage,
+    api: &dyn Api,
+    name: &String,
+    source: &TokenSource,
+) -> Result<(), ContractError> {
+    let source = match source {
+        TokenSource::Denom(denom) => {
+            if denom.trim().is_empty() {
+                return Err(ContractError::ValidationError {
+                    reason: "token denom must be non-empty".to_owned(),
+                });
+            }
+            TokenSource::Denom(denom.to_owned())
+        },
+        TokenSource::Contract(address) => TokenSource::Contract(api.addr_validate(address.as_str())?),
+    };
+    let key = source.to_key();
+    if !CAMPAIGN_TOKEN_SOURCES.has(store, (name, &key))
+        && CAMPAIGN_TOKEN_SOURCES
+            .prefix(name)
+            .keys(store, None, None, Order::Ascending)
+            .count()
+            >= MAX_TOKEN_SOURCES
+    {
+        return Err(ContractError::ValidationError {
+            reason: format!("campaigns can accept at most {} token sources", MAX_TOKEN_SOURCES),
+        });
+    }
+    CAMPAIGN_TOKEN_SOURCES.save(store, (name, &key), &source)?;
+    Ok(())
+}
+
+/// Ensure that unclaimed deposits from the source can be burned once a campaign
+/// expires.
+fn ensure_burnable(
+    store: &dyn Storage,
+    contract: &Addr,
+    source: &TokenSource,
+) -> Result<(), ContractError> {
+    if !is_burnable(source, contract, CONFIG.load(store)?.tokenfactory.as_ref()) {
+        return Err(ContractError::ValidationError {
+            reason: format!("{} cannot be burned by this contract", source.to_key()),
+        });
+    }
+    Ok(())
+}
+
+/// Can the contract burn tokens from the source? Native denoms can only be
+/// burned if they're tokenfactory denoms created by this contract.
+pub fn is_burnable(
+    source: &TokenSource,
+    contract: &Addr,
+    tokenfactory: Option<&TokenFactory>,
+) -> bool {
+    match source {
+        TokenSource::Denom(denom) => tokenfactory.is_some() && denom.starts_with(&format!("factory/{}/", contract)),
+        TokenSource::Contract(_) => true,
+    }
+}
+
+/// Ensure that the campaign accepts deposits of the given token, so that
+/// records can't be created for tokens from arbitrary contracts.
+pub fn ensure_token_allowed(
+    store: &dyn Storage,
+    name: &String,
+    token: &Token,
+) -> Result<(), ContractError> {
+    if !CAMPAIGN_TOKEN_SOURCES.has(store, (name, &token.source().to_key())) {
+        return Err(ContractError::ValidationError {
+            reason: format!("campaign {} does not accept token {}", name, token.to_key()),
+        });
+    }
+    Ok(())
+}
+
+/// Increment a campaign's deposited and outstanding totals for the token,
+/// ensuring that the total doesn't exceed the mint cap of a minted token.
+pub fn record_deposit(
+    store: &mut dyn Storage,
+    campaign: &mut Campaign,
+    token: &Token,
+    amount: Uint128,
+) -> Result<(), ContractError> {
+    let totals = update_token_totals(store, campaign, token, |totals| {
+        totals.deposited = add_u128(totals.deposited, amount)?;
+        totals.outstanding = add_u128(totals.outstanding, amount)?;
+        Ok(())
+    })?;
+    if let Some(mint) = &campaign.mint {
+        if mint.token == *token && totals.deposited > mint.cap {
+            return Err(ContractError::ValidationError {
+                reason: format!("upserted total exceeds mint cap of {}", mint.cap),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Does the campaign mint the token on claim rather than hold deposits?
+pub fn is_minted(
+    campaign: &Campaign,
+    token: &Token,
+) -> bool {
+    campaign.mint.as_ref().map(|m| m.token == *token).unwrap_or(false)
+}
+
+/// Move a claimed amount from the campaign's outstanding to claimed total,
+/// finalizing the campaign if it's closed and nothing remains outstanding.
+pub fn record_claim(
+    store: &mut dyn Storage,
+    name: &String,
+    token: &Token,
+    amount: Uint128,
+) -> Result<(), ContractError> {
+    if let Some(mut campaign) = CAMPAIGNS.may_load(store, name)? {
+        update_token_totals(store, &mut campaign, token, |totals| {
+            totals.claimed = add_u128(totals.claimed, amount)?;
+            totals.outstanding = sub_u128(totals.outstanding, amount)?;
+            Ok(())
+        })?;
+        if campaign.status == CampaignStatus::Closed && campaign.n_outstanding_tokens == 0 {
+            campaign.status = CampaignStatus::Finalized;
+        }
+        CAMPAIGNS.save(store, name, &campaign)?;
+    }
+    Ok(())
+}
+
+/// Move an amount back from the campaign's claimed to outstanding total, as
+/// when a claim paid out over IBC is returned, reopening the campaign for
+/// claims if it was finalized. A returned amount of a minted token has already
+/// been minted, so it's set aside to pay for later claims.
+pub fn record_claim_reversal(
+    store: &mut dyn Storage,
+    name: &String,
+    token: &Token,
+    amount: Uint128,
+) -> Result<(), ContractError> {
+    if let Some(mut campaign) = CAMPAIGNS.may_load(store, name)? {
+        let is_mint = is_minted(&campaign, token);
+        update_token_totals(store, &mut campaign, token, |totals| {
+            totals.claimed = sub_u128(totals.claimed, amount)?;
+            totals.outstanding = add_u128(totals.outstanding, amount)?;
+            if is_mint {
+                totals.returned = add_u128(totals.returned, amount)?;
+            }
+            Ok(())
+        })?;
+        if campaign.status == CampaignStatus::Finalized {
+            campaign.status = CampaignStatus::Closed;
+        }
+        CAMPAIGNS.save(store, name, &campaign)?;
+    }
+    Ok(())
+}
+
+/// Take as much as possible of a claimed amount of a minted token out of what
+/// was returned to the campaign, returning the remainder that must be minted.
+pub fn draw_returned_mint(
+    store: &mut dyn Storage,
+    name: &String,
+    token: &Token,
+    amount: Uint128,
+) -> Result<Uint128, ContractError> {
+    let token_key = token.to_key();
+    if let Some(mut totals) = CAMPAIGN_TOKEN_TOTALS.may_load(store, (name, &token_key))? {
+        let drawn = amount.min(totals.returned);
+        if !drawn.is_zero() {
+            totals.returned = sub_u128(totals.returned, drawn)?;
+            CAMPAIGN_TOKEN_TOTALS.save(store, (name, &token_key), &totals)?;
+            return sub_u128(amount, drawn);
+        }
+    }
+    Ok(amount)
+}
+
+/// Move a recovered amount from the campaign's outstanding to recovered total.
+pub fn record_recovery(
+    store: &mut dyn Storage,
+    campaign: &mut Campaign,
+    token: &Token,
+    amount: Uint128,
+) -> Result<(), ContractError> {
+    update_token_totals(store, campaign, token, |totals| {
+        totals.recovered = add_u128(totals.recovered, amount)?;
+        totals.outstanding = sub_u128(totals.outstanding, amount)?;
+        Ok(())
+    })?;
+    Ok(())
+}
+
+/// Move an amount back from the campaign's recovered to outstanding total, as
+/// when a refund fails, reopening the campaign if it was finalized. As with
+/// claims, a returned amount of a minted token is set aside for later claims.
+pub fn record_recovery_reversal(
+    store: &mut dyn Storage,
+    name: &String,
+    token: &Token,
+    amount: Uint128,
+) -> Result<(), ContractError> {
+    if let Some(mut campaign) = CAMPAIGNS.may_load(store, name)? {
+        let is_mint = is_minted(&campaign, token);
+        update_token_totals(store, &mut campaign, token, |totals| {
+            totals.recovered = sub_u128(totals.recovered, amount)?;
+            totals.outstanding = add_u128(totals.outstanding, amount)?;
+            if is_mint {
+                totals.returned = add_u128(totals.returned, amount)?;
+            }
+            Ok(())
+        })?;
+        if campaign.status == CampaignStatus::Finalized {
+            campaign.status = CampaignStatus::Closed;
+        }
+        CAMPAIGNS.save(store, name, &campaign)?;
+    }
+    Ok(())
+}
+
+/// Apply a change to a campaign's totals for the token, keeping count of the
+/// tokens that still have an amount outstanding. The caller saves the campaign.
+fn update_token_totals(
+    store: &mut dyn Storage,
+    campaign: &mut Campaign,
+    token: &Token,
+    update: impl FnOnce(&mut CampaignTokenTotals) -> Result<(), ContractError>,
+) -> Result<CampaignTokenTotals, ContractError> {
+    let name = campaign.name.to_owned();
+    let token_key = token.to_key();
+
+    let mut totals = CAMPAIGN_TOKEN_TOTALS
+        .may_load(store, (&name, &token_key))?
+        .unwrap_or_else(|| CampaignTokenTotals {
+            token: token.to_owned(),
+            deposited: Uint128::zero(),
+            claimed: Uint128::zero(),
+            recovered: Uint128::zero(),
+            outstanding: Uint128::zero(),
+            returned: Uint128::zero(),
+        });
+
+    let was_outstanding = !totals.outstanding.is_zero();
+    update(&mut totals)?;
+
+    match (was_outstanding, totals.outstanding.is_zero()) {
+        (false, false) => campaign.n_outstanding_tokens = add_u32(campaign.n_outstanding_tokens, 1)?,
+        (true, true) => campaign.n_outstanding_tokens = sub_u32(campaign.n_outstanding_tokens, 1)?,
+        _ => {},
+    }
+
+    CAMPAIGN_TOKEN_TOTALS.save(store, (&name, &token_key), &totals)?;
+    Ok(totals)
+}
+
+/// Is the given time within the campaign's claim window?
+pub fn is_within_claim_window(
+    campaign: &Campaign,
+    time: Timestamp,
+) -> bool {
+    campaign.claim_start.map(|t| time >= t).unwrap_or(true) && campaign.claim_end.map(|t| time < t).unwrap_or(true)
+}
+
+pub fn load_campaign(
+    store: &dyn Storage,
+    name: &String,
+) -> Result<Campaign, ContractError> {
+    CAMPAIGNS
+        .may_load(store, name)?
+        .ok_or_else(|| ContractError::ValidationError {
+            reason: format!("campaign {} does not exist", name),
+        })
+}
+
+pub fn load_campaign_for_creator(
+    store: &dyn Storage,
+    name: &String,
+    sender: &Addr,
+) -> Result<Campaign, ContractError> {
+    let campaign = load_campaign(store, name)?;
+    if campaign.creator != sender {
+        return Err(ContractError::NotAuthorized {
+            reason: "only the campaign creator can perform this action".to_owned(),
+        });
+    }
+    Ok(campaign)
+}
+
+/// Load a campaign, ensuring that the sender is its creator or the account
+/// that IBC hooks executes as on behalf of its remote depositor.
+pub fn load_campaign_for_depositor(
+    store: &dyn Storage,
+    env: &Env,
+    name: &String,
+    sender: &Addr,
+) -> Result<Campaign, ContractError> {
+    let campaign = load_campaign(store, name)?;
+    if campaign.creator != sender {
+        if let Some(remote) = &campaign.remote_depositor {
+            validate_remote_sender(env, sender, remote)?;
+        } else {
+            return Err(ContractError::NotAuthorized {
+                reason: "only the campaign creator can perform this action".to_owned(),
+            });
+        }
+    }
+    Ok(campaign)
+}
+
+fn validate_remote_depositor(remote: &RemoteSender) -> Result<(), ContractError> {
+    if remote.channel_id.trim().is_empty() || remote.address.trim().is_empty() {
+        return Err(ContractError::ValidationError {
+            reason: "remote depositor channel and address must be non-empty".to_owned(),
+        });
+    }
+    Ok(())
+}
+
+fn validate_text(
+    field: &str,
+    value: &str,
+    max_len: usize,
+) -> Result<(), ContractError> {
+    if value.trim().is_empty() || value.len() > max_len {
+        return Err(ContractError::ValidationError {
+            reason: format!("{} must be non-empty and at most {} bytes", field, max_len),
+        });
+    }
+    Ok(())
+}
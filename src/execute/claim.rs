@@ -2,15 +2,28 @@ use std::collections::HashMap;
 
 use crate::{
     error::ContractError,
-    math::add_u128,
-    models::ClaimRecord,
-    msg::ClaimMsg,
-    state::{ADDR_2_CLAIM_ID, AMOUNT_2_CLAIM_ID, CLAIM_RECORDS, TIME_2_CLAIM_ID, TOKEN_2_CLAIM_ID},
-    token::Token,
+    events::{claim_event, CLAIM_PAID},
+    math::{add_u128, add_u64, mul_pct_u128, sub_u128},
+    models::{ClaimHistoryEntry, ClaimRecord},
+    msg::{ClaimData, ClaimDelta, ClaimFilter, ClaimMsg},
+    state::{
+        ADDR_2_CLAIM_ID, ADDR_2_HISTORY_ID, ADDR_NAME_2_CLAIM_ID, AMOUNT_2_CLAIM_ID, CLAIMED_TOTALS, CLAIM_HISTORY,
+        CLAIM_RECORDS, HISTORY_COUNTER, NAME_2_HISTORY_ID, NAME_ADDR_2_CLAIM_ID, NAME_AMOUNT_2_CLAIM_ID,
+        PENDING_TOTALS, TIME_2_CLAIM_ID, TOKEN_2_CLAIM_ID,
+    },
+    token::{transfer_all, Token, TokenAmount},
 };
-use cosmwasm_std::{attr, Addr, Order, Response, Storage, SubMsg, Uint128};
+use cosmwasm_std::{attr, to_json_binary, Addr, BlockInfo, Event, Order, Response, Storage, SubMsg, Uint128, Uint64};
+use cw_storage_plus::Bound;
 
-use super::Context;
+use super::{
+    campaign::{is_within_claim_window, load_campaign, record_claim},
+    foreign::verify_key_proof,
+    hooks::{callback_submsgs, claim_hook_submsgs},
+    ibc::ibc_transfer_submsgs,
+    mint::mint_submsgs,
+    Context,
+};
 
 pub const DEFAULT_LIMIT: usize = 100;
 pub const DEFAULT_SUBMSG_LIMIT: usize = 30;
@@ -19,71 +32,505 @@ pub fn exec_claim(
     ctx: Context,
     msg: ClaimMsg,
 ) -> Result<Response, ContractError> {
-    let Context { deps, info, .. } = ctx;
+    let Context { deps, info, env } = ctx;
+    let ClaimMsg {
+        ids,
+        recipient,
+        filter,
+        cursor,
+        amount,
+        ibc,
+        proof,
+    } = msg;
+
+    if ibc.is_some() && recipient.is_some() {
+        return Err(ContractError::ValidationError {
+            reason: "cannot claim to both a recipient and an IBC destination".to_owned(),
+        });
+    }
+
+    let recipient = if let Some(recipient) = recipient {
+        deps.api.addr_validate(recipient.as_str())?
+    } else {
+        info.sender.to_owned()
+    };
+
+    // Claim on behalf of the address derived from a public key, if the sender
+    // proves control of it.
+    let claimant = if let Some(proof) = &proof {
+        verify_key_proof(deps.api, &env, &info.sender, proof)?
+    } else {
+        info.sender.to_owned()
+    };
+
+    let plan = plan_claim(deps.storage, &env.block, &claimant, ids, filter, cursor, amount)?;
+
+    let mut events: Vec<Event> = Vec::with_capacity(plan.ids.len());
+    let mut deltas: Vec<ClaimDelta> = Vec::with_capacity(plan.ids.len());
+    if let Some(amount) = plan.partial_amount {
+        if let Some(claim) = process_partial_claim(deps.storage, &env.block, &claimant, plan.ids[0], amount)? {
+            events.push(claim_event(CLAIM_PAID, &claim, amount, claim.amount));
+            deltas.push(ClaimDelta { claim, delta: amount });
+        }
+    } else {
+        for id in plan.ids.iter() {
+            if let Some((mut claim, _)) = process_claim(deps.storage, &env.block, &claimant, *id, None)? {
+                events.push(claim_event(CLAIM_PAID, &claim, claim.amount, Uint128::zero()));
+                let delta = claim.amount;
+                claim.amount = Uint128::zero();
+                deltas.push(ClaimDelta { claim, delta });
+            }
+        }
+    }
+
+    // Craete transfer submsgs for claimed token types
+    let payouts = plan.payouts();
+    let transfer_submsgs: Vec<SubMsg> = if let Some(ibc) = &ibc {
+        ibc_transfer_submsgs(deps.storage, &env, &claimant, ibc, &deltas)?
+    } else {
+        transfer_all(&env.contract.address, &recipient, &payouts)?
+    };
+
+    let mint_submsgs = mint_submsgs(deps.storage, &env.contract.address, &deltas)?;
+    let callback_submsgs = callback_submsgs(deps.storage, &deltas)?;
+    let hook_submsgs = claim_hook_submsgs(deps.storage, &deltas)?;
+
+    let mut attrs = vec![
+        attr("action", "claim"),
+        attr("claimant", claimant),
+        attr(
+            "recipient",
+            ibc.map(|x| x.remote_address).unwrap_or_else(|| recipient.to_string()),
+        ),
+        attr("has_more", plan.has_more.to_string()),
+    ];
+    // Attribute values can't be empty, so only include the cursor if set
+    if let Some(cursor) = plan.cursor {
+        attrs.push(attr("cursor", cursor.to_string()));
+    }
+
+    Ok(Response::new()
+        .add_attributes(attrs)
+        .set_data(to_json_binary(&ClaimData {
+            payouts,
+            has_more: plan.has_more,
+            cursor: plan.cursor,
+        })?)
+        .add_events(events)
+        .add_submessages(mint_submsgs)
+        .add_submessages(transfer_submsgs)
+        .add_submessages(callback_submsgs)
+        .add_submessages(hook_submsgs))
+}
+
+/// Claim records selected for a claim along with the totals to pay out.
+pub struct ClaimPlan {
+    pub ids: Vec<u64>,
+    pub totals: HashMap<String, (Token, Uint128)>,
+    /// When claiming everything, whether records remain beyond the limits.
+    pub has_more: bool,
+    /// When claiming everything, ID of the last record considered, from which
+    /// the next claim should resume.
+    pub cursor: Option<Uint64>,
+    /// Amount to claim from the single selected record, if less than its
+    /// full balance.
+    pub partial_amount: Option<Uint128>,
+}
+
+impl ClaimPlan {
+    /// Total amount to pay out for each token, ordered by token key.
+    pub fn payouts(&self) -> Vec<TokenAmount> {
+        let mut payouts: Vec<(&String, TokenAmount)> = self
+            .totals
+            .iter()
+            .map(|(key, (token, amount))| {
+                (
+                    key,
+                    TokenAmount {
+                        token: token.to_owned(),
+                        amount: *amount,
+                    },
+                )
+            })
+            .collect();
+        payouts.sort_by(|a, b| a.0.cmp(b.0));
+        payouts.into_iter().map(|x| x.1).collect()
+    }
+}
+
+/// Select the claim records that a claim by the given claimant would consume
+/// and aggregate total amounts by token, without modifying state. When
+/// claiming everything, selection stops at DEFAULT_LIMIT records or once the
+/// token types and campaign callbacks would need more than
+/// DEFAULT_SUBMSG_LIMIT submsgs, whichever comes first.
+pub fn plan_claim(
+    store: &dyn Storage,
+    block: &BlockInfo,
+    claimant: &Addr,
+    ids: Option<Vec<Uint64>>,
+    filter: Option<ClaimFilter>,
+    cursor: Option<Uint64>,
+    amount: Option<Uint128>,
+) -> Result<ClaimPlan, ContractError> {
+    let is_explicit = ids.is_some();
+
+    if amount.is_some() && ids.as_ref().map(|ids| ids.len() != 1).unwrap_or(true) {
+        return Err(ContractError::ValidationError {
+            reason: "partial claims must specify exactly one claim ID".to_owned(),
+        });
+    }
+    let filter = filter.unwrap_or(ClaimFilter {
+        tokens: None,
+        names: None,
+        min_amount: None,
+    });
 
     // Collect together IDs of claim records to process
-    let ids = if let Some(ids) = msg.ids {
+    let (ids, mut has_more) = if let Some(ids) = ids {
         if ids.len() > DEFAULT_LIMIT {
             return Err(ContractError::ValidationError {
                 reason: format!("cannot claim more than {} records per tx", DEFAULT_LIMIT),
             });
         }
-
-        ids
+        (ids.into_iter().map(|id| id.u64()).collect(), false)
     } else {
-        ADDR_2_CLAIM_ID
-            .keys(deps.storage, None, None, Order::Ascending)
-            .map(|r| r.unwrap().1.into())
-            .take(DEFAULT_LIMIT)
-            .collect()
+        select_claim_ids(store, claimant, &filter, cursor.map(|id| id.u64()))?
     };
 
+    let token_keys: Option<Vec<String>> = filter
+        .tokens
+        .as_ref()
+        .map(|tokens| tokens.iter().map(|t| t.to_key()).collect());
+
     // Storage to agg total amounts for each token type being claimed
     let mut totals: HashMap<String, (Token, Uint128)> = HashMap::with_capacity(8);
+    let mut claimed_ids: Vec<u64> = Vec::with_capacity(ids.len());
+    let mut last_id: Option<u64> = None;
 
-    // Try to prevent overly large transactions that would run out of gas and
-    // confuse the end-user.
-    if totals.len() > DEFAULT_SUBMSG_LIMIT {
-        return Err(ContractError::ValidationError {
-            reason: format!("cannot claim more than {} token types at per tx", DEFAULT_SUBMSG_LIMIT),
-        });
-    }
+    // Cache of whether each campaign encountered is within its claim window
+    // and whether it has a callback, along with the campaigns whose callbacks
+    // will be notified, each of which takes a submsg.
+    let mut campaigns: HashMap<String, (bool, bool)> = HashMap::with_capacity(4);
+    let mut callback_names: Vec<String> = Vec::with_capacity(4);
 
     // Aggregate total amounts fo each token being claimed
     for id in ids {
-        if let Some(claim) = process_claim(deps.storage, &info.sender, id.into())? {
+        if claimed_ids.contains(&id) || !ADDR_2_CLAIM_ID.has(store, (claimant, id)) {
+            last_id = Some(id);
+            continue;
+        }
+        if let Some(claim) = CLAIM_RECORDS.may_load(store, id)? {
             let key = claim.token.to_key();
+
+            // Skip records that don't match the filter
+            if token_keys.as_ref().map(|keys| !keys.contains(&key)).unwrap_or(false)
+                || filter
+                    .names
+                    .as_ref()
+                    .map(|names| !names.contains(&claim.name))
+                    .unwrap_or(false)
+                || filter.min_amount.map(|n| claim.amount < n).unwrap_or(false)
+            {
+                last_id = Some(id);
+                continue;
+            }
+
+            // Skip records outside of their campaign's claim window, or error
+            // out if the claimant explicitly asked for them.
+            let (is_claimable, has_callback) = if let Some(val) = campaigns.get(&claim.name) {
+                *val
+            } else {
+                let campaign = load_campaign(store, &claim.name)?;
+                let val = (
+                    is_within_claim_window(&campaign, block.time),
+                    campaign.callback.is_some(),
+                );
+                campaigns.insert(claim.name.to_owned(), val);
+                val
+            };
+            if !is_claimable {
+                if is_explicit {
+                    return Err(ContractError::ValidationError {
+                        reason: format!("claim {} is outside of its campaign's claim window", id),
+                    });
+                }
+                last_id = Some(id);
+                continue;
+            }
+
+            // Try to prevent overly large transactions that would run out of
+            // gas and confuse the end-user. Each token type takes a transfer
+            // submsg, and each campaign with a callback takes another.
+            let is_new_callback = has_callback && !callback_names.contains(&claim.name);
+            let n_submsgs = totals.len()
+                + callback_names.len()
+                + if totals.contains_key(&key) { 0 } else { 1 }
+                + if is_new_callback { 1 } else { 0 };
+            if n_submsgs > DEFAULT_SUBMSG_LIMIT {
+                if is_explicit {
+                    return Err(ContractError::ValidationError {
+                        reason: format!("claim would need more than {} submsgs per tx", DEFAULT_SUBMSG_LIMIT),
+                    });
+                }
+                has_more = true;
+                break;
+            }
+
+            last_id = Some(id);
+            if is_new_callback {
+                callback_names.push(claim.name.to_owned());
+            }
+
             if let Some(val) = totals.get_mut(&key) {
                 val.1 = add_u128(val.1, claim.amount)?;
             } else {
                 totals.insert(key, (claim.token, claim.amount));
             }
+            claimed_ids.push(id);
         }
     }
 
-    // Craete transfer submsgs for claimed token types
-    let mut transfer_submsgs: Vec<SubMsg> = Vec::with_capacity(totals.len());
-    for (token, amount) in totals.values() {
-        transfer_submsgs.push(token.transfer(&info.sender, *amount)?);
+    // Pay out only the requested amount of a partial claim
+    let mut partial_amount: Option<Uint128> = None;
+    if let Some(amount) = amount {
+        if let Some(val) = totals.values_mut().next() {
+            if amount.is_zero() || amount > val.1 {
+                return Err(ContractError::ValidationError {
+                    reason: "partial claim amount must be non-zero and at most the claim's amount".to_owned(),
+                });
+            }
+            if amount < val.1 {
+                val.1 = amount;
+                partial_amount = Some(amount);
+            }
+        }
     }
 
-    Ok(Response::new()
-        .add_attributes(vec![attr("action", "claim"), attr("claimant", info.sender)])
-        .add_submessages(transfer_submsgs))
+    Ok(ClaimPlan {
+        ids: claimed_ids,
+        totals,
+        has_more,
+        partial_amount,
+        cursor: if has_more {
+            last_id.or(cursor.map(|id| id.u64())).map(|id| id.into())
+        } else {
+            None
+        },
+    })
+}
+
+/// Get up to DEFAULT_LIMIT of the claimant's claim IDs after the cursor, in
+/// ascending order, ranging over whichever index most narrowly matches the
+/// filter, along with whether more remain.
+fn select_claim_ids(
+    store: &dyn Storage,
+    claimant: &Addr,
+    filter: &ClaimFilter,
+    cursor: Option<u64>,
+) -> Result<(Vec<u64>, bool), ContractError> {
+    let mut ids: Vec<u64> = Vec::with_capacity(DEFAULT_LIMIT + 1);
+
+    if let Some(tokens) = &filter.tokens {
+        for token in tokens.iter() {
+            for result in TOKEN_2_CLAIM_ID
+                .prefix((claimant, &token.to_key()))
+                .keys(store, cursor.map(Bound::exclusive), None, Order::Ascending)
+                .take(DEFAULT_LIMIT + 1)
+            {
+                ids.push(result?);
+            }
+        }
+    } else if let Some(names) = &filter.names {
+        for name in names.iter() {
+            for result in ADDR_NAME_2_CLAIM_ID
+                .prefix((claimant, name))
+                .keys(store, cursor.map(Bound::exclusive), None, Order::Ascending)
+                .take(DEFAULT_LIMIT + 1)
+            {
+                ids.push(result?);
+            }
+        }
+    } else {
+        for result in ADDR_2_CLAIM_ID
+            .prefix(claimant)
+            .keys(store, cursor.map(Bound::exclusive), None, Order::Ascending)
+            .take(DEFAULT_LIMIT + 1)
+        {
+            ids.push(result?);
+        }
+    }
+
+    // Merge IDs from multiple index prefixes into a single ascending sequence
+    ids.sort_unstable();
+    ids.dedup();
+
+    let has_more = ids.len() > DEFAULT_LIMIT;
+    ids.truncate(DEFAULT_LIMIT);
+
+    Ok((ids, has_more))
+}
+
+/// Return claim record whilst deleting all references to it from storage,
+/// recording the amount as claimed in its campaign's totals and appending it to
+/// the claim history. Any keeper fee taken out of the claim is returned along
+/// with it, and only the remainder is recorded as received by the recipient.
+pub fn process_claim(
+    store: &mut dyn Storage,
+    block: &BlockInfo,
+    recipient: &Addr,
+    id: u64,
+    keeper_fee_pct: Option<Uint128>,
+) -> Result<Option<(ClaimRecord, Uint128)>, ContractError> {
+    let claim = if let Some(claim) = remove_claim(store, recipient, id)? {
+        claim
+    } else {
+        return Ok(None);
+    };
+
+    let fee = if let Some(pct) = keeper_fee_pct {
+        mul_pct_u128(claim.amount, pct)?
+    } else {
+        Uint128::zero()
+    };
+
+    record_claim(store, &claim.name, &claim.token, claim.amount)?;
+
+    let received = ClaimRecord {
+        amount: sub_u128(claim.amount, fee)?,
+        ..claim.clone()
+    };
+    record_history(store, block, recipient, &received, false)?;
+
+    Ok(Some((claim, fee)))
+}
+
+/// Deduct an amount from a claim record, leaving the remainder pending, while
+/// recording the amount as claimed and appending it to the claim history.
+/// Returns the updated claim record.
+pub fn process_partial_claim(
+    store: &mut dyn Storage,
+    block: &BlockInfo,
+    recipient: &Addr,
+    id: u64,
+    amount: Uint128,
+) -> Result<Option<ClaimRecord>, ContractError> {
+    if !ADDR_2_CLAIM_ID.has(store, (recipient, id)) {
+        return Ok(None);
+    }
+
+    let mut claim = CLAIM_RECORDS.load(store, id)?;
+    let prev_amount = claim.amount;
+
+    claim.amount = sub_u128(claim.amount, amount)?;
+    CLAIM_RECORDS.save(store, id, &claim)?;
+
+    AMOUNT_2_CLAIM_ID.remove(store, (recipient, prev_amount.u128(), id));
+    AMOUNT_2_CLAIM_ID.save(store, (recipient, claim.amount.u128(), id), &())?;
+    NAME_AMOUNT_2_CLAIM_ID.remove(store, (&claim.name, prev_amount.u128(), id));
+    NAME_AMOUNT_2_CLAIM_ID.save(store, (&claim.name, claim.amount.u128(), id), &())?;
+
+    // Update recipient's pending total for the token
+    let token_key = claim.token.to_key();
+    if let Some(mut total) = PENDING_TOTALS.may_load(store, (recipient, &token_key))? {
+        total.amount = sub_u128(total.amount, amount)?;
+        PENDING_TOTALS.save(store, (recipient, &token_key), &total)?;
+    }
+
+    let claimed = ClaimRecord {
+        amount,
+        ..claim.clone()
+    };
+    record_claim(store, &claimed.name, &claimed.token, amount)?;
+    record_history(store, block, recipient, &claimed, false)?;
+
+    Ok(Some(claim))
+}
+
+/// Append a receipt for the amount of the claim received by the recipient to
+/// the history log and increment the recipient's lifetime claimed total for
+/// the token. A reversal instead records that a received amount was returned
+/// and re-credited, decrementing the total.
+pub fn record_history(
+    store: &mut dyn Storage,
+    block: &BlockInfo,
+    recipient: &Addr,
+    claim: &ClaimRecord,
+    is_reversal: bool,
+) -> Result<(), ContractError> {
+    let history_id = HISTORY_COUNTER
+        .update(store, |n| -> Result<_, ContractError> { add_u64(n, 1u64) })?
+        .u64();
+
+    CLAIM_HISTORY.save(
+        store,
+        history_id,
+        &ClaimHistoryEntry {
+            id: claim.id,
+            name: claim.name.to_owned(),
+            token: claim.token.to_owned(),
+            amount: claim.amount,
+            recipient: recipient.to_owned(),
+            claimed_at: block.time,
+            height: block.height,
+            is_reversal,
+        },
+    )?;
+
+    ADDR_2_HISTORY_ID.save(store, (recipient, history_id), &())?;
+    NAME_2_HISTORY_ID.save(store, (&claim.name, history_id), &())?;
+
+    CLAIMED_TOTALS.update(
+        store,
+        (recipient, &claim.token.to_key()),
+        |maybe_total| -> Result<_, ContractError> {
+            let mut total = maybe_total.unwrap_or_else(|| TokenAmount {
+                token: claim.token.to_owned(),
+                amount: Uint128::zero(),
+            });
+            total.amount = if is_reversal {
+                sub_u128(total.amount, claim.amount)?
+            } else {
+                add_u128(total.amount, claim.amount)?
+            };
+            Ok(total)
+        },
+    )?;
+
+    Ok(())
 }
 
 /// Return claim record whilst deleting all references to it from storage.
-fn process_claim(
+/// Records that don't belong to the given recipient are ignored.
+pub fn remove_claim(
     store: &mut dyn Storage,
     recipient: &Addr,
     id: u64,
 ) -> Result<Option<ClaimRecord>, ContractError> {
+    if !ADDR_2_CLAIM_ID.has(store, (recipient, id)) {
+        return Ok(None);
+    }
     Ok(if let Some(claim) = CLAIM_RECORDS.may_load(store, id)? {
         CLAIM_RECORDS.remove(store, id);
         AMOUNT_2_CLAIM_ID.remove(store, (recipient, claim.amount.u128(), id));
         TIME_2_CLAIM_ID.remove(store, (recipient, claim.updated_at.nanos(), id));
         TOKEN_2_CLAIM_ID.remove(store, (recipient, &claim.token.to_key(), id));
         ADDR_2_CLAIM_ID.remove(store, (recipient, id));
+        ADDR_NAME_2_CLAIM_ID.remove(store, (recipient, &claim.name, id));
+        NAME_AMOUNT_2_CLAIM_ID.remove(store, (&claim.name, claim.amount.u128(), id));
+        NAME_ADDR_2_CLAIM_ID.remove(store, (&claim.name, recipient, id));
+
+        // Update recipient's pending total for the token
+        let token_key = claim.token.to_key();
+        if let Some(mut total) = PENDING_TOTALS.may_load(store, (recipient, &token_key))? {
+            total.amount = sub_u128(total.amount, claim.amount)?;
+            total.count = total.count.saturating_sub(1);
+            if total.count == 0 {
+                PENDING_TOTALS.remove(store, (recipient, &token_key));
+            } else {
+                PENDING_TOTALS.save(store, (recipient, &token_key), &total)?;
+            }
+        }
+
         Some(claim)
     } else {
         None
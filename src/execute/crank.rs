@@ -0,0 +1,60 @@
+use cosmwasm_std::{Addr, Order, Storage};
+use cw_storage_plus::{Bound, Map};
+
+use crate::{error::ContractError, state::ID_LUTAB};
+
+use super::claim::DEFAULT_LIMIT;
+
+/// Visit a batch of a campaign's claim records in lookup table order, resuming
+/// from the cursor saved in `cursors` by the previous batch. The visitor gets
+/// each record's token key, recipient and ID, and returns false to stop before
+/// handling a record. Scans at most `limit` entries, then saves a cursor to
+/// resume from, or removes it once every record has been visited. Returns
+/// whether every record has been visited.
+pub fn crank<F>(
+    store: &mut dyn Storage,
+    cursors: &Map<&String, (String, Addr)>,
+    name: &String,
+    limit: Option<u32>,
+    mut visit: F,
+) -> Result<bool, ContractError>
+where
+    F: FnMut(&mut dyn Storage, String, Addr, u64) -> Result<bool, ContractError>,
+{
+    // Max number of lookup table entries to scan in this tx
+    let limit = limit
+        .map(|n| (n as usize).clamp(1, DEFAULT_LIMIT))
+        .unwrap_or(DEFAULT_LIMIT);
+
+    let cursor = cursors.may_load(store, name)?;
+    let min_bound = cursor
+        .as_ref()
+        .map(|(token_key, recipient)| Bound::exclusive((token_key, recipient)));
+
+    let entries = ID_LUTAB
+        .sub_prefix(name)
+        .range(store, min_bound, None, Order::Ascending)
+        .take(limit + 1)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut next_cursor: Option<(String, Addr)> = None;
+    let mut is_done = true;
+
+    for (i, ((token_key, recipient), id)) in entries.into_iter().enumerate() {
+        // Stop once we've scanned up to the limit or the visitor is full,
+        // leaving a cursor to resume from next time.
+        if i == limit || !visit(store, token_key.clone(), recipient.clone(), id.u64())? {
+            is_done = false;
+            break;
+        }
+        next_cursor = Some((token_key, recipient));
+    }
+
+    if is_done {
+        cursors.remove(store, name);
+    } else if let Some(cursor) = next_cursor {
+        cursors.save(store, name, &cursor)?;
+    }
+
+    Ok(is_done)
+}
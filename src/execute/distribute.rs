@@ -0,0 +1,112 @@
+use crate::{
+    error::ContractError,
+    events::{claim_event, CLAIM_PAID},
+    math::sub_u128,
+    models::{Payout, PayoutKind},
+    msg::ClaimDelta,
+    state::{CONFIG, DISTRIBUTION_CURSORS},
+};
+use cosmwasm_std::{attr, Event, Response, Uint128};
+
+use super::{
+    campaign::{is_within_claim_window, load_campaign},
+    claim::{process_claim, DEFAULT_SUBMSG_LIMIT},
+    crank::crank,
+    hooks::{callback_submsgs, claim_hook_submsgs},
+    mint::mint_submsgs,
+    payout::payout_submsgs,
+    Context,
+};
+
+/// Push pending claims in the given campaign out to their recipients,
+/// resuming from wherever the previous call left off.
+pub fn exec_distribute(
+    ctx: Context,
+    name: String,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let Context { deps, info, env } = ctx;
+    let config = CONFIG.load(deps.storage)?;
+
+    if let Some(distributor) = &config.distributor {
+        if info.sender != distributor {
+            return Err(ContractError::NotAuthorized {
+                reason: "only the distributor can execute distribute".to_owned(),
+            });
+        }
+    }
+
+    let campaign = load_campaign(deps.storage, &name)?;
+    if !is_within_claim_window(&campaign, env.block.time) {
+        return Err(ContractError::ValidationError {
+            reason: format!("campaign {} is outside of its claim window", name),
+        });
+    }
+
+    let mut payouts: Vec<Payout> = Vec::with_capacity(DEFAULT_SUBMSG_LIMIT);
+    let mut events: Vec<Event> = Vec::with_capacity(DEFAULT_SUBMSG_LIMIT);
+    let mut deltas: Vec<ClaimDelta> = Vec::with_capacity(DEFAULT_SUBMSG_LIMIT);
+    let mut n_distributed: usize = 0;
+
+    // Each claim paid takes one submsg, plus one for its keeper fee, if any,
+    // and the campaign's callback takes one for the whole batch.
+    let n_submsgs_per_claim = if config.keeper_fee_pct.is_some() { 2 } else { 1 };
+    let n_callback_submsgs = if campaign.callback.is_some() { 1 } else { 0 };
+
+    let is_done = crank(
+        deps.storage,
+        &DISTRIBUTION_CURSORS,
+        &name,
+        limit,
+        |store, _, recipient, id| {
+            // Stop if paying this claim would exceed the max number of submsgs
+            if (payouts.len() + 1) * n_submsgs_per_claim + n_callback_submsgs > DEFAULT_SUBMSG_LIMIT {
+                return Ok(false);
+            }
+
+            if let Some((mut claim, fee)) = process_claim(store, &env.block, &recipient, id, config.keeper_fee_pct)? {
+                // The caller's fee is paid once the claim's transfer succeeds
+                payouts.push(Payout {
+                    name: name.to_owned(),
+                    kind: PayoutKind::Claim,
+                    recipient: recipient.to_owned(),
+                    token: claim.token.to_owned(),
+                    amount: sub_u128(claim.amount, fee)?,
+                    fee: if fee.is_zero() {
+                        None
+                    } else {
+                        Some((info.sender.to_owned(), fee))
+                    },
+                });
+                events.push(claim_event(CLAIM_PAID, &claim, claim.amount, Uint128::zero()));
+                n_distributed += 1;
+
+                let delta = claim.amount;
+                claim.amount = Uint128::zero();
+                deltas.push(ClaimDelta { claim, delta });
+            }
+            Ok(true)
+        },
+    )?;
+
+    // Transfers that fail are re-credited rather than reverting the batch, so
+    // that one unpayable record can't keep the cursor from moving past it.
+    let transfer_submsgs = payout_submsgs(deps.storage, &env, payouts)?;
+
+    let mint_submsgs = mint_submsgs(deps.storage, &env.contract.address, &deltas)?;
+    let callback_submsgs = callback_submsgs(deps.storage, &deltas)?;
+    let hook_submsgs = claim_hook_submsgs(deps.storage, &deltas)?;
+
+    Ok(Response::new()
+        .add_attributes(vec![
+            attr("action", "distribute"),
+            attr("name", name),
+            attr("distributed", n_distributed.to_string()),
+            attr("done", is_done.to_string()),
+        ])
+        .add_events(events)
+        .add_submessages(mint_submsgs)
+        .add_submessages(transfer_submsgs)
+        .add_submessages(callback_submsgs)
+        .add_submessages(hook_submsgs))
+}
@@ -0,0 +1,96 @@
+use crate::{
+    error::ContractError,
+    msg::{ForeignRecipient, KeyProof, UpsertMsg},
+};
+use bech32::{FromBase32, ToBase32, Variant};
+use cosmwasm_std::{Addr, Api, Env};
+use ripemd::Ripemd160;
+use sha2::{Digest, Sha256};
+
+/// Move foreign recipients' amounts into the upsert's local amounts.
+pub fn resolve_foreign_amounts(
+    api: &dyn Api,
+    env: &Env,
+    mut msg: UpsertMsg,
+) -> Result<UpsertMsg, ContractError> {
+    if let Some(foreign_amounts) = msg.foreign_amounts.take() {
+        for (recipient, amount) in foreign_amounts {
+            msg.amounts
+                .push((resolve_foreign_recipient(api, env, &recipient)?, amount));
+        }
+    }
+    Ok(msg)
+}
+
+/// Get the local address corresponding to a foreign recipient.
+pub fn resolve_foreign_recipient(
+    api: &dyn Api,
+    env: &Env,
+    recipient: &ForeignRecipient,
+) -> Result<Addr, ContractError> {
+    let bytes = match recipient {
+        ForeignRecipient::PubKey(pubkey) => pubkey_to_address_bytes(pubkey.as_slice())?,
+        ForeignRecipient::Bech32(address) => {
+            let (_, data, _) = bech32::decode(address).map_err(|e| ContractError::ValidationError {
+                reason: format!("invalid bech32 address {}: {}", address, e),
+            })?;
+            Vec::<u8>::from_base32(&data).map_err(|e| ContractError::ValidationError {
+                reason: format!("invalid bech32 address {}: {}", address, e),
+            })?
+        },
+    };
+
+    let address = bech32::encode(&local_prefix(env)?, bytes.to_base32(), Variant::Bech32).map_err(|e| {
+        ContractError::ValidationError {
+            reason: format!("cannot encode local address: {}", e),
+        }
+    })?;
+
+    // Ensure the result has the same canonical bytes as a native address
+    Ok(api.addr_humanize(&api.addr_canonicalize(&address)?)?)
+}
+
+/// Verify that the claimant signed over the contract address and its own
+/// address with the given public key, returning the key's derived address.
+pub fn verify_key_proof(
+    api: &dyn Api,
+    env: &Env,
+    claimant: &Addr,
+    proof: &KeyProof,
+) -> Result<Addr, ContractError> {
+    let hash = Sha256::new()
+        .chain_update(env.contract.address.as_bytes())
+        .chain_update(claimant.as_bytes())
+        .finalize();
+
+    let is_valid = api
+        .secp256k1_verify(&hash, &proof.signature, &proof.pubkey)
+        .map_err(|e| ContractError::ValidationError {
+            reason: format!("cannot verify signature: {}", e),
+        })?;
+    if !is_valid {
+        return Err(ContractError::NotAuthorized {
+            reason: "invalid public key signature".to_owned(),
+        });
+    }
+
+    resolve_foreign_recipient(api, env, &ForeignRecipient::PubKey(proof.pubkey.to_owned()))
+}
+
+/// Standard Cosmos SDK account address: ripemd160(sha256(pubkey)).
+fn pubkey_to_address_bytes(pubkey: &[u8]) -> Result<Vec<u8>, ContractError> {
+    if pubkey.len() != 33 {
+        return Err(ContractError::ValidationError {
+            reason: "public key must be a 33-byte compressed secp256k1 key".to_owned(),
+        });
+    }
+    Ok(Ripemd160::digest(Sha256::digest(pubkey)).to_vec())
+}
+
+/// Bech32 prefix of addresses on this chain.
+fn local_prefix(env: &Env) -> Result<String, ContractError> {
+    let (hrp, _, _) = bech32::decode(env.contract.address.as_str()).map_err(|e| ContractError::ValidationError {
+        reason: format!("invalid contract address: {}", e),
+    })?;
+    Ok(hrp)
+}
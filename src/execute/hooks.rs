@@ -0,0 +1,231 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    error::ContractError,
+    msg::{CallbackExecuteMsg, ClaimDelta, ClaimHookMsg, ClaimPaidMsg, ClaimPayout, HookExecuteMsg, UpsertHookMsg},
+    state::{CAMPAIGN_HOOKS, GLOBAL_HOOKS, OWNER},
+};
+use cosmwasm_std::{attr, to_json_binary, Addr, Attribute, Order, Reply, Response, Storage, SubMsg, WasmMsg};
+
+use super::{campaign::load_campaign, Context};
+
+pub const HOOK_REPLY_ID: u64 = 1;
+pub const CALLBACK_REPLY_ID: u64 = 2;
+pub const MAX_HOOKS: usize = 10;
+/// Gas a hook may use, so that one running out can't abort the whole tx,
+/// since out-of-gas errors are never caught by replies.
+pub const HOOK_GAS_LIMIT: u64 = 300_000;
+/// Gas a campaign callback may use, for the same reason.
+pub const CALLBACK_GAS_LIMIT: u64 = 300_000;
+
+pub fn exec_add_hook(
+    ctx: Context,
+    address: Addr,
+    name: Option<String>,
+) -> Result<Response, ContractError> {
+    let Context { deps, info, .. } = ctx;
+    ensure_owner(deps.storage, &info.sender)?;
+
+    let address = deps.api.addr_validate(address.as_str())?;
+
+    let n_hooks = if let Some(name) = &name {
+        load_campaign(deps.storage, name)?;
+        CAMPAIGN_HOOKS.save(deps.storage, (name, &address), &())?;
+        CAMPAIGN_HOOKS
+            .prefix(name)
+            .keys(deps.storage, None, None, Order::Ascending)
+            .count()
+    } else {
+        GLOBAL_HOOKS.save(deps.storage, &address, &())?;
+        GLOBAL_HOOKS.keys(deps.storage, None, None, Order::Ascending).count()
+    };
+
+    if n_hooks > MAX_HOOKS {
+        return Err(ContractError::ValidationError {
+            reason: format!("cannot register more than {} hooks", MAX_HOOKS),
+        });
+    }
+
+    Ok(Response::new().add_attributes(hook_attrs("add_hook", address, name)))
+}
+
+pub fn exec_remove_hook(
+    ctx: Context,
+    address: Addr,
+    name: Option<String>,
+) -> Result<Response, ContractError> {
+    let Context { deps, info, .. } = ctx;
+    ensure_owner(deps.storage, &info.sender)?;
+
+    if let Some(name) = &name {
+        CAMPAIGN_HOOKS.remove(deps.storage, (name, &address));
+    } else {
+        GLOBAL_HOOKS.remove(deps.storage, &address);
+    }
+
+    Ok(Response::new().add_attributes(hook_attrs("remove_hook", address, name)))
+}
+
+/// Build submsgs notifying hooks of paid claims. Global hooks receive every
+/// claim, whereas campaign hooks only receive claims in their campaign.
+pub fn claim_hook_submsgs(
+    store: &dyn Storage,
+    claims: &[ClaimDelta],
+) -> Result<Vec<SubMsg>, ContractError> {
+    if claims.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let global_hooks = load_global_hooks(store)?;
+    let mut hook_claims: BTreeMap<Addr, Vec<ClaimDelta>> = BTreeMap::new();
+
+    for hook in global_hooks.iter() {
+        hook_claims.insert(hook.to_owned(), claims.to_vec());
+    }
+
+    for claim in claims.iter() {
+        for hook in load_campaign_hooks(store, &claim.claim.name)? {
+            if !global_hooks.contains(&hook) {
+                hook_claims.entry(hook).or_default().push(claim.to_owned());
+            }
+        }
+    }
+
+    hook_claims
+        .into_iter()
+        .map(|(hook, claims)| build_hook_submsg(&hook, &HookExecuteMsg::ClaimHook(ClaimHookMsg { claims })))
+        .collect()
+}
+
+/// Build submsgs notifying global hooks and hooks for the campaign of claims
+/// created or increased by an upsert.
+pub fn upsert_hook_submsgs(
+    store: &dyn Storage,
+    name: &String,
+    depositor: &Addr,
+    claims: &[ClaimDelta],
+) -> Result<Vec<SubMsg>, ContractError> {
+    if claims.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let mut hooks = load_global_hooks(store)?;
+    for hook in load_campaign_hooks(store, name)? {
+        if !hooks.contains(&hook) {
+            hooks.push(hook);
+        }
+    }
+
+    let msg = HookExecuteMsg::UpsertHook(UpsertHookMsg {
+        name: name.to_owned(),
+        depositor: depositor.to_owned(),
+        claims: claims.to_vec(),
+    });
+
+    hooks.iter().map(|hook| build_hook_submsg(hook, &msg)).collect()
+}
+
+/// Hook failures are isolated from the tx that triggered them, so that a
+/// broken hook can't block claims or upserts.
+pub fn reply_hook(reply: Reply) -> Result<Response, ContractError> {
+    Ok(match reply.result.into_result() {
+        Ok(_) => Response::new(),
+        Err(error) => Response::new().add_attributes(vec![attr("action", "hook_failed"), attr("error", error)]),
+    })
+}
+
+/// Build a submsg for each campaign with a callback, notifying it of all the
+/// claims paid out of the campaign.
+pub fn callback_submsgs(
+    store: &dyn Storage,
+    claims: &[ClaimDelta],
+) -> Result<Vec<SubMsg>, ContractError> {
+    let mut payouts: BTreeMap<String, Vec<ClaimPayout>> = BTreeMap::new();
+    for ClaimDelta { claim, delta } in claims.iter() {
+        payouts.entry(claim.name.to_owned()).or_default().push(ClaimPayout {
+            recipient: claim.recipient.to_owned(),
+            token: claim.token.to_owned(),
+            amount: *delta,
+        });
+    }
+
+    let mut submsgs: Vec<SubMsg> = Vec::with_capacity(payouts.len());
+    for (name, payouts) in payouts.into_iter() {
+        if let Some(callback) = load_campaign(store, &name)?.callback {
+            let msg = WasmMsg::Execute {
+                contract_addr: callback.to_string(),
+                msg: to_json_binary(&CallbackExecuteMsg::ClaimPaid(ClaimPaidMsg { name, payouts }))?,
+                funds: vec![],
+            };
+            submsgs.push(SubMsg::reply_always(msg, CALLBACK_REPLY_ID).with_gas_limit(CALLBACK_GAS_LIMIT));
+        }
+    }
+
+    Ok(submsgs)
+}
+
+/// Callback failures are swallowed just like hook failures, so a depositor's
+/// callback can never block a claim.
+pub fn reply_callback(reply: Reply) -> Result<Response, ContractError> {
+    Ok(match reply.result.into_result() {
+        Ok(_) => Response::new(),
+        Err(error) => Response::new().add_attributes(vec![attr("action", "callback_failed"), attr("error", error)]),
+    })
+}
+
+/// Attributes of a hook being added or removed. Global hooks have no campaign
+/// name, and attributes can't be empty.
+fn hook_attrs(
+    action: &str,
+    address: Addr,
+    name: Option<String>,
+) -> Vec<Attribute> {
+    let mut attrs = vec![attr("action", action), attr("address", address)];
+    if let Some(name) = name {
+        attrs.push(attr("name", name));
+    }
+    attrs
+}
+
+fn build_hook_submsg(
+    hook: &Addr,
+    msg: &HookExecuteMsg,
+) -> Result<SubMsg, ContractError> {
+    Ok(SubMsg::reply_on_error(
+        WasmMsg::Execute {
+            contract_addr: hook.to_string(),
+            msg: to_json_binary(msg)?,
+            funds: vec![],
+        },
+        HOOK_REPLY_ID,
+    )
+    .with_gas_limit(HOOK_GAS_LIMIT))
+}
+
+fn load_global_hooks(store: &dyn Storage) -> Result<Vec<Addr>, ContractError> {
+    Ok(GLOBAL_HOOKS
+        .keys(store, None, None, Order::Ascending)
+        .collect::<Result<Vec<_>, _>>()?)
+}
+
+fn load_campaign_hooks(
+    store: &dyn Storage,
+    name: &String,
+) -> Result<Vec<Addr>, ContractError> {
+    Ok(CAMPAIGN_HOOKS
+        .prefix(name)
+        .keys(store, None, None, Order::Ascending)
+        .collect::<Result<Vec<_>, _>>()?)
+}
+
+fn ensure_owner(
+    store: &dyn Storage,
+    sender: &Addr,
+) -> Result<(), ContractError> {
+    if *sender != OWNER.load(store)? {
+        return Err(ContractError::NotAuthorized {
+            reason: "only the owner can manage hooks".to_owned(),
+        });
+    }
+    Ok(())
+}
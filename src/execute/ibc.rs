@@ -0,0 +1,297 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    error::ContractError,
+    events::{claim_event, CLAIM_RESTORED},
+    math::add_u128,
+    models::{Campaign, IbcTransfer, Payout, PayoutKind, RemoteSender},
+    msg::{ClaimDelta, IbcDestination, IbcLifecycleComplete},
+    proto::ProtoBuf,
+    state::{next_reply_id, IBC_TRANSFERS, IBC_TRANSFER_COUNTER, PENDING_IBC_TRANSFERS},
+    token::Token,
+};
+use cosmwasm_std::{
+    attr, Addr, Binary, CosmosMsg, DepsMut, Env, Event, Reply, Response, Storage, SubMsg, Timestamp, Uint128,
+};
+
+use bech32::{ToBase32, Variant};
+use sha2::{Digest, Sha256};
+use std::ops::Range;
+
+use super::payout::restore_payout;
+
+/// Reply IDs reserved for IBC transfers, each of which is kept pending under
+/// the reply ID of its submsg until the reply reveals its packet sequence.
+pub const IBC_TRANSFER_REPLY_IDS: Range<u64> = (2 << 32)..(3 << 32);
+pub const DEFAULT_IBC_TIMEOUT_SECONDS: u64 = 600;
+pub const MAX_IBC_TIMEOUT_SECONDS: u64 = 7 * 24 * 60 * 60;
+
+const TRANSFER_PORT: &str = "transfer";
+const MSG_TRANSFER_TYPE_URL: &str = "/ibc.applications.transfer.v1.MsgTransfer";
+const IBC_HOOKS_SENDER_PREFIX: &str = "ibc-wasm-hook-intermediary";
+
+/// Build ICS-20 transfer submsgs sending the total claimed for each token to
+/// the remote address. Transfers carry an ibc-hooks `ibc_callback` memo so
+/// that the chain reports their outcome back to the contract via sudo.
+pub fn ibc_transfer_submsgs(
+    store: &mut dyn Storage,
+    env: &Env,
+    claimant: &Addr,
+    destination: &IbcDestination,
+    claims: &[ClaimDelta],
+) -> Result<Vec<SubMsg>, ContractError> {
+    let IbcDestination {
+        channel_id,
+        remote_address,
+        timeout_seconds,
+    } = destination;
+
+    if channel_id.is_empty() || remote_address.is_empty() {
+        return Err(ContractError::ValidationError {
+            reason: "IBC channel and remote address are required".to_owned(),
+        });
+    }
+
+    // Aggregate claimed amounts into one transfer per token
+    let mut transfers: BTreeMap<String, IbcTransfer> = BTreeMap::new();
+    for ClaimDelta { claim, delta } in claims.iter() {
+        if claim.token.get_denom().is_none() {
+            return Err(ContractError::ValidationError {
+                reason: "only native tokens can be claimed over IBC".to_owned(),
+            });
+        }
+        let transfer = transfers.entry(claim.token.to_key()).or_insert_with(|| IbcTransfer {
+            kind: PayoutKind::Claim,
+            claimant: claimant.to_owned(),
+            channel_id: channel_id.to_owned(),
+            token: claim.token.to_owned(),
+            amount: Uint128::zero(),
+            claims: vec![],
+        });
+        transfer.amount = add_u128(transfer.amount, *delta)?;
+        transfer.claims.push((claim.name.to_owned(), *delta));
+    }
+
+    let timeout_seconds = timeout_seconds.unwrap_or(DEFAULT_IBC_TIMEOUT_SECONDS);
+    if timeout_seconds == 0 || timeout_seconds > MAX_IBC_TIMEOUT_SECONDS {
+        return Err(ContractError::ValidationError {
+            reason: format!("IBC timeout must be between 1 and {} seconds", MAX_IBC_TIMEOUT_SECONDS),
+        });
+    }
+
+    let timeout = env.block.time.plus_seconds(timeout_seconds);
+
+    let mut submsgs: Vec<SubMsg> = Vec::with_capacity(transfers.len());
+    for transfer in transfers.into_values() {
+        submsgs.push(tracked_transfer_submsg(store, env, transfer, remote_address, timeout)?);
+    }
+
+    Ok(submsgs)
+}
+
+/// Build a submsg returning funds to a remote depositor over the channel its
+/// deposits arrived on. Like claims sent over IBC, the transfer is tracked so
+/// that it's re-credited to the campaign's creator if it fails or times out.
+pub fn remote_refund_submsg(
+    store: &mut dyn Storage,
+    env: &Env,
+    campaign: &Campaign,
+    remote: &RemoteSender,
+    token: &Token,
+    amount: Uint128,
+) -> Result<SubMsg, ContractError> {
+    if token.get_denom().is_none() {
+        return Err(ContractError::ValidationError {
+            reason: "only native tokens can be returned over IBC".to_owned(),
+        });
+    }
+
+    let transfer = IbcTransfer {
+        kind: PayoutKind::Refund,
+        claimant: campaign.creator.to_owned(),
+        channel_id: remote.channel_id.to_owned(),
+        token: token.to_owned(),
+        amount,
+        claims: vec![(campaign.name.to_owned(), amount)],
+    };
+    let timeout = env.block.time.plus_seconds(DEFAULT_IBC_TIMEOUT_SECONDS);
+
+    tracked_transfer_submsg(store, env, transfer, &remote.address, timeout)
+}
+
+/// Build the submsg for a transfer, keeping the transfer pending under the
+/// submsg's reply ID until its reply reveals the packet sequence number.
+fn tracked_transfer_submsg(
+    store: &mut dyn Storage,
+    env: &Env,
+    transfer: IbcTransfer,
+    receiver: &str,
+    timeout: Timestamp,
+) -> Result<SubMsg, ContractError> {
+    let msg = msg_transfer(
+        env,
+        &transfer.channel_id,
+        receiver,
+        &transfer.token,
+        transfer.amount,
+        timeout,
+        &callback_memo(env),
+    );
+    let reply_id = next_reply_id(store, &IBC_TRANSFER_COUNTER, &IBC_TRANSFER_REPLY_IDS)?;
+    PENDING_IBC_TRANSFERS.save(store, reply_id, &transfer)?;
+    Ok(SubMsg::reply_on_success(msg, reply_id))
+}
+
+/// Ensure that the local sender is the intermediate account that IBC hooks
+/// executes as on behalf of the remote sender, which is the bech32 encoding
+/// of sha256(sha256(prefix) | "<channel>/<remote sender>").
+pub fn validate_remote_sender(
+    env: &Env,
+    sender: &Addr,
+    remote: &RemoteSender,
+) -> Result<(), ContractError> {
+    let (hrp, _, _) = bech32::decode(env.contract.address.as_str()).map_err(|e| ContractError::ValidationError {
+        reason: format!("invalid contract address: {}", e),
+    })?;
+
+    let mut hasher = Sha256::new();
+    hasher.update(Sha256::digest(IBC_HOOKS_SENDER_PREFIX.as_bytes()));
+    hasher.update(format!("{}/{}", remote.channel_id, remote.address).as_bytes());
+    let intermediate_sender = bech32::encode(&hrp, hasher.finalize().to_base32(), Variant::Bech32).map_err(|e| {
+        ContractError::ValidationError {
+            reason: format!("cannot derive intermediate sender: {}", e),
+        }
+    })?;
+
+    if *sender != intermediate_sender {
+        return Err(ContractError::NotAuthorized {
+            reason: "sender does not correspond to the remote sender".to_owned(),
+        });
+    }
+    Ok(())
+}
+
+/// Key the pending transfer by the sequence number of the packet it sent, so
+/// that its outcome can be matched up when the chain reports it.
+pub fn reply_ibc_transfer(
+    deps: DepsMut,
+    reply: Reply,
+) -> Result<Response, ContractError> {
+    let response = reply
+        .result
+        .into_result()
+        .map_err(|error| ContractError::ValidationError {
+            reason: format!("IBC transfer failed: {}", error),
+        })?;
+    let sequence = response
+        .events
+        .iter()
+        .filter(|e| e.ty == "send_packet")
+        .flat_map(|e| e.attributes.iter())
+        .find(|a| a.key == "packet_sequence")
+        .and_then(|a| a.value.parse::<u64>().ok())
+        .ok_or_else(|| ContractError::ValidationError {
+            reason: "IBC transfer did not emit a packet sequence".to_owned(),
+        })?;
+
+    let transfer =
+        PENDING_IBC_TRANSFERS
+            .may_load(deps.storage, reply.id)?
+            .ok_or_else(|| ContractError::ValidationError {
+                reason: format!("no IBC transfer awaiting reply {}", reply.id),
+            })?;
+
+    PENDING_IBC_TRANSFERS.remove(deps.storage, reply.id);
+
+    IBC_TRANSFERS.save(deps.storage, (&transfer.channel_id, sequence), &transfer)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "ibc_transfer"),
+        attr("channel", transfer.channel_id),
+        attr("sequence", sequence.to_string()),
+    ]))
+}
+
+/// Forget a transfer once acknowledged, or re-credit its claims to the
+/// claimant, or its refund to the campaign's creator, if it failed or timed
+/// out.
+pub fn sudo_ibc_lifecycle(
+    deps: DepsMut,
+    env: Env,
+    msg: IbcLifecycleComplete,
+) -> Result<Response, ContractError> {
+    let (channel, sequence, is_success) = match msg {
+        IbcLifecycleComplete::IbcAck {
+            channel,
+            sequence,
+            success,
+            ..
+        } => (channel, sequence, success),
+        IbcLifecycleComplete::IbcTimeout { channel, sequence } => (channel, sequence, false),
+    };
+
+    let transfer = if let Some(transfer) = IBC_TRANSFERS.may_load(deps.storage, (&channel, sequence))? {
+        transfer
+    } else {
+        return Ok(Response::new());
+    };
+
+    IBC_TRANSFERS.remove(deps.storage, (&channel, sequence));
+
+    let mut events: Vec<Event> = Vec::with_capacity(transfer.claims.len());
+    if !is_success {
+        for (name, amount) in transfer.claims.into_iter() {
+            let payout = Payout {
+                name,
+                kind: transfer.kind.to_owned(),
+                recipient: transfer.claimant.to_owned(),
+                token: transfer.token.to_owned(),
+                amount,
+                fee: None,
+            };
+            let (claim, restored) = restore_payout(deps.storage, &env.block, &payout)?;
+            events.push(claim_event(CLAIM_RESTORED, &claim, restored, claim.amount));
+        }
+    }
+
+    Ok(Response::new()
+        .add_attributes(vec![
+            attr("action", "ibc_lifecycle_complete"),
+            attr("channel", channel),
+            attr("sequence", sequence.to_string()),
+            attr("success", is_success.to_string()),
+        ])
+        .add_events(events))
+}
+
+/// ibc-hooks memo asking the chain to report the transfer's outcome via sudo.
+fn callback_memo(env: &Env) -> String {
+    format!("{{\"ibc_callback\":\"{}\"}}", env.contract.address)
+}
+
+fn msg_transfer(
+    env: &Env,
+    channel_id: &str,
+    receiver: &str,
+    token: &Token,
+    amount: Uint128,
+    timeout: Timestamp,
+    memo: &str,
+) -> CosmosMsg {
+    let coin = ProtoBuf::new()
+        .string(1, &token.get_denom().unwrap_or_default())
+        .string(2, &amount.to_string());
+    let value = ProtoBuf::new()
+        .string(1, TRANSFER_PORT)
+        .string(2, channel_id)
+        .message(3, coin)
+        .string(4, env.contract.address.as_str())
+        .string(5, receiver)
+        .uint64(7, timeout.nanos())
+        .string(8, memo);
+
+    CosmosMsg::Stargate {
+        type_url: MSG_TRANSFER_TYPE_URL.to_owned(),
+        value: Binary::from(value.into_vec()),
+    }
+}
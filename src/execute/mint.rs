@@ -0,0 +1,53 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{error::ContractError, math::add_u128, msg::ClaimDelta, state::CONFIG, token::Token};
+use cosmwasm_std::{Addr, Storage, SubMsg, Uint128};
+
+use super::campaign::{draw_returned_mint, is_minted, load_campaign};
+
+/// Build submsgs minting to the contract whatever portion of the paid claims
+/// belongs to mint-on-claim campaigns, so that payouts can then be transferred
+/// as usual. Amounts minted before but returned by failed payouts are used up
+/// first. Must precede the transfer submsgs.
+pub fn mint_submsgs(
+    store: &mut dyn Storage,
+    contract: &Addr,
+    claims: &[ClaimDelta],
+) -> Result<Vec<SubMsg>, ContractError> {
+    let mut minted: BTreeMap<String, (Token, Uint128)> = BTreeMap::new();
+    let mut campaign_mints: HashMap<String, bool> = HashMap::with_capacity(4);
+
+    for ClaimDelta { claim, delta } in claims.iter() {
+        let key = format!("{}/{}", claim.name, claim.token.to_key());
+        let is_mint = if let Some(is_mint) = campaign_mints.get(&key) {
+            *is_mint
+        } else {
+            let is_mint = is_minted(&load_campaign(store, &claim.name)?, &claim.token);
+            campaign_mints.insert(key, is_mint);
+            is_mint
+        };
+        if is_mint {
+            let amount = draw_returned_mint(store, &claim.name, &claim.token, *delta)?;
+            if amount.is_zero() {
+                continue;
+            }
+            if let Some(val) = minted.get_mut(&claim.token.to_key()) {
+                val.1 = add_u128(val.1, amount)?;
+            } else {
+                minted.insert(claim.token.to_key(), (claim.token.to_owned(), amount));
+            }
+        }
+    }
+
+    if minted.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let tokenfactory = CONFIG.load(store)?.tokenfactory;
+    let mut submsgs: Vec<SubMsg> = Vec::with_capacity(minted.len());
+    for (token, amount) in minted.values() {
+        submsgs.push(token.mint(contract, *amount, tokenfactory.as_ref())?);
+    }
+
+    Ok(submsgs)
+}
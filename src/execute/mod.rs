@@ -1,4 +1,13 @@
+pub mod campaign;
 pub mod claim;
+pub mod crank;
+pub mod distribute;
+pub mod foreign;
+pub mod hooks;
+pub mod ibc;
+pub mod mint;
+pub mod payout;
+pub mod recover;
 pub mod set_config;
 pub mod upsert;
 
@@ -0,0 +1,136 @@
+use crate::{
+    error::ContractError,
+    events::{claim_event, CLAIM_RESTORED},
+    math::add_u128,
+    models::{ClaimRecord, Payout, PayoutKind},
+    state::{next_reply_id, PAYOUT_COUNTER, PENDING_PAYOUTS},
+};
+use cosmwasm_std::{attr, BlockInfo, DepsMut, Env, Reply, Response, Storage, SubMsg, Uint128};
+use std::ops::Range;
+
+use super::{
+    campaign::{record_claim_reversal, record_recovery_reversal},
+    claim::record_history,
+    upsert::credit_claim,
+};
+
+/// Reply IDs reserved for payouts. Each payout is kept pending under the reply
+/// ID of its transfer, so that payouts made by nested executions can't be
+/// mistaken for one another.
+pub const PAYOUT_REPLY_IDS: Range<u64> = (1 << 32)..(2 << 32);
+
+/// Build a transfer submsg for each payout, keeping the payouts pending so
+/// that any whose transfer fails can be re-credited when its reply arrives.
+pub fn payout_submsgs(
+    store: &mut dyn Storage,
+    env: &Env,
+    payouts: Vec<Payout>,
+) -> Result<Vec<SubMsg>, ContractError> {
+    let mut submsgs: Vec<SubMsg> = Vec::with_capacity(payouts.len());
+
+    for payout in payouts.into_iter() {
+        // Zero-value transfers fail for most tokens, so there's nothing to send
+        if payout.amount.is_zero() {
+            continue;
+        }
+        let submsg = payout
+            .token
+            .transfer(&env.contract.address, &payout.recipient, payout.amount)?;
+        let reply_id = next_reply_id(store, &PAYOUT_COUNTER, &PAYOUT_REPLY_IDS)?;
+        PENDING_PAYOUTS.save(store, reply_id, &payout)?;
+        submsgs.push(SubMsg::reply_always(submsg.msg, reply_id));
+    }
+
+    Ok(submsgs)
+}
+
+/// Forget the payout that the reply belongs to, paying the keeper's fee on it
+/// if its transfer succeeded, or re-crediting it as a claim to its recipient
+/// if the transfer failed.
+pub fn reply_payout(
+    deps: DepsMut,
+    env: Env,
+    reply: Reply,
+) -> Result<Response, ContractError> {
+    let payout = PENDING_PAYOUTS
+        .may_load(deps.storage, reply.id)?
+        .ok_or_else(|| ContractError::ValidationError {
+            reason: format!("no payout awaiting reply {}", reply.id),
+        })?;
+
+    PENDING_PAYOUTS.remove(deps.storage, reply.id);
+
+    let error = match reply.result.into_result() {
+        Ok(_) => {
+            // Now that the claim has been paid, pay the keeper its fee
+            let fee_submsgs = if let Some((keeper, fee)) = payout.fee {
+                let fee_payout = Payout {
+                    name: payout.name,
+                    kind: PayoutKind::KeeperFee,
+                    recipient: keeper,
+                    token: payout.token,
+                    amount: fee,
+                    fee: None,
+                };
+                payout_submsgs(deps.storage, &env, vec![fee_payout])?
+            } else {
+                vec![]
+            };
+            return Ok(Response::new().add_submessages(fee_submsgs));
+        },
+        Err(error) => error,
+    };
+
+    let (claim, restored) = restore_payout(deps.storage, &env.block, &payout)?;
+
+    Ok(Response::new()
+        .add_attributes(vec![
+            attr("action", "payout_failed"),
+            attr("name", payout.name),
+            attr("error", error),
+        ])
+        .add_event(claim_event(CLAIM_RESTORED, &claim, restored, claim.amount)))
+}
+
+/// Re-credit the amount of a payout that never reached its recipient as a
+/// claim, along with any keeper fee taken out of it, undoing its effect on
+/// the campaign's totals and, for claims, recording the reversal in the
+/// recipient's claim history. Returns the claim and the amount re-credited.
+pub fn restore_payout(
+    store: &mut dyn Storage,
+    block: &BlockInfo,
+    payout: &Payout,
+) -> Result<(ClaimRecord, Uint128), ContractError> {
+    let Payout {
+        name,
+        kind,
+        recipient,
+        token,
+        amount,
+        fee,
+    } = payout;
+
+    // A claim that wasn't paid is re-credited in full, fee included
+    let restored = if let Some((_, fee)) = fee {
+        add_u128(*amount, *fee)?
+    } else {
+        *amount
+    };
+
+    let (claim, _) = credit_claim(store, block.time, name, token, recipient, restored)?;
+
+    match kind {
+        PayoutKind::Claim => {
+            record_claim_reversal(store, name, token, restored)?;
+            let returned = ClaimRecord {
+                amount: *amount,
+                ..claim.clone()
+            };
+            record_history(store, block, recipient, &returned, true)?;
+        },
+        PayoutKind::KeeperFee => record_claim_reversal(store, name, token, restored)?,
+        PayoutKind::Refund => record_recovery_reversal(store, name, token, restored)?,
+    }
+
+    Ok((claim, restored))
+}
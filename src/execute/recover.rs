@@ -0,0 +1,232 @@
+use std::collections::HashMap;
+
+use crate::{
+    error::ContractError,
+    events::{claim_event, CLAIM_REVOKED},
+    math::{add_u128, sub_u128},
+    models::{Campaign, CampaignStatus, ExpiryAction, Payout, PayoutKind},
+    state::{CAMPAIGNS, CONFIG, RECOVERY_CURSORS},
+    token::{Token, TokenAmount},
+};
+use cosmwasm_std::{attr, Addr, Env, Event, Response, Storage, SubMsg, Uint128};
+
+use super::{
+    campaign::{
+        draw_returned_mint, is_burnable, is_minted, load_campaign, load_campaign_for_depositor, record_recovery,
+    },
+    claim::{remove_claim, DEFAULT_LIMIT, DEFAULT_SUBMSG_LIMIT},
+    crank::crank,
+    ibc::remote_refund_submsg,
+    payout::payout_submsgs,
+    Context,
+};
+
+/// Result of sweeping a batch of unclaimed records out of an expired campaign.
+struct Sweep {
+    totals: HashMap<String, (Token, Uint128)>,
+    events: Vec<Event>,
+    n_recovered: usize,
+    is_done: bool,
+}
+
+/// Return whatever remains unclaimed in an expired campaign to its creator,
+/// resuming from wherever the previous call left off.
+pub fn exec_recover(
+    ctx: Context,
+    name: String,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let Context { deps, info, env } = ctx;
+    let mut campaign = load_campaign_for_depositor(deps.storage, &env, &name, &info.sender)?;
+
+    if campaign.expiry_action != ExpiryAction::Refund {
+        return Err(ContractError::ValidationError {
+            reason: format!("campaign {} does not refund unclaimed funds", name),
+        });
+    }
+
+    let sweep = sweep_expired(deps.storage, &env, &mut campaign, limit)?;
+    let transfer_submsgs = refund_submsgs(deps.storage, &env, &campaign, &info.sender, &sweep.totals)?;
+
+    Ok(Response::new()
+        .add_attributes(vec![
+            attr("action", "recover"),
+            attr("name", name),
+            attr("recovered", sweep.n_recovered.to_string()),
+            attr("done", sweep.is_done.to_string()),
+        ])
+        .add_events(sweep.events)
+        .add_submessages(transfer_submsgs))
+}
+
+/// Apply an expired campaign's expiry action to whatever remains unclaimed,
+/// resuming from wherever the previous call left off. Anyone may execute this.
+pub fn exec_finalize_expired(
+    ctx: Context,
+    name: String,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let Context { deps, env, .. } = ctx;
+    let mut campaign = load_campaign(deps.storage, &name)?;
+
+    let sweep = sweep_expired(deps.storage, &env, &mut campaign, limit)?;
+
+    let mut events: Vec<Event> = Vec::with_capacity(sweep.totals.len());
+    let submsgs: Vec<SubMsg> = match &campaign.expiry_action {
+        ExpiryAction::Refund => refund_submsgs(deps.storage, &env, &campaign, &campaign.creator, &sweep.totals)?,
+        ExpiryAction::SendTo(address) => refund_submsgs(deps.storage, &env, &campaign, address, &sweep.totals)?,
+        ExpiryAction::Burn => {
+            let tokenfactory = CONFIG.load(deps.storage)?.tokenfactory;
+            let mut submsgs: Vec<SubMsg> = Vec::with_capacity(sweep.totals.len());
+            let mut unburnable: HashMap<String, (Token, Uint128)> = HashMap::new();
+            for (token_key, (token, amount)) in sweep.totals.iter() {
+                if amount.is_zero() {
+                    continue;
+                }
+                // Refund anything that can no longer be burned, as when the
+                // tokenfactory module has since been unconfigured.
+                if !is_burnable(&token.source(), &env.contract.address, tokenfactory.as_ref()) {
+                    unburnable.insert(token_key.to_owned(), (token.to_owned(), *amount));
+                    continue;
+                }
+                submsgs.push(token.burn(&env.contract.address, *amount, tokenfactory.as_ref())?);
+                events.push(Event::new("tokens_burned").add_attributes(vec![
+                    attr("name", name.to_owned()),
+                    attr("token", token_key),
+                    attr("amount", amount.to_string()),
+                ]));
+            }
+            submsgs.extend(refund_submsgs(
+                deps.storage,
+                &env,
+                &campaign,
+                &campaign.creator,
+                &unburnable,
+            )?);
+            submsgs
+        },
+    };
+
+    Ok(Response::new()
+        .add_attributes(vec![
+            attr("action", "finalize_expired"),
+            attr("name", name),
+            attr("recovered", sweep.n_recovered.to_string()),
+            attr("done", sweep.is_done.to_string()),
+        ])
+        .add_events(sweep.events)
+        .add_events(events)
+        .add_submessages(submsgs))
+}
+
+/// Revoke up to `limit` unclaimed records of a campaign whose claim window has
+/// ended, aggregating their totals by token and saving a cursor to resume from
+/// if any remain. Finalizes the campaign once nothing remains outstanding.
+fn sweep_expired(
+    store: &mut dyn Storage,
+    env: &Env,
+    campaign: &mut Campaign,
+    limit: Option<u32>,
+) -> Result<Sweep, ContractError> {
+    let name = campaign.name.to_owned();
+
+    if campaign.claim_end.map(|t| env.block.time < t).unwrap_or(true) {
+        return Err(ContractError::ValidationError {
+            reason: format!("campaign {} has not reached the end of its claim window", name),
+        });
+    }
+
+    let mut totals: HashMap<String, (Token, Uint128)> = HashMap::with_capacity(4);
+    let mut events: Vec<Event> = Vec::with_capacity(DEFAULT_LIMIT);
+    let mut n_recovered: usize = 0;
+
+    let is_done = crank(
+        store,
+        &RECOVERY_CURSORS,
+        &name,
+        limit,
+        |store, token_key, recipient, id| {
+            // Stop if this claim would exceed the max number of token types
+            if !totals.contains_key(&token_key) && totals.len() == DEFAULT_SUBMSG_LIMIT {
+                return Ok(false);
+            }
+
+            if let Some(claim) = remove_claim(store, &recipient, id)? {
+                record_recovery(store, campaign, &claim.token, claim.amount)?;
+                events.push(claim_event(CLAIM_REVOKED, &claim, claim.amount, Uint128::zero()));
+
+                // Minted tokens were never deposited, so the only amount held
+                // is whatever was minted for payouts that were returned.
+                let amount = if is_minted(campaign, &claim.token) {
+                    let unminted = draw_returned_mint(store, &name, &claim.token, claim.amount)?;
+                    sub_u128(claim.amount, unminted)?
+                } else {
+                    claim.amount
+                };
+                if let Some(val) = totals.get_mut(&token_key) {
+                    val.1 = add_u128(val.1, amount)?;
+                } else {
+                    totals.insert(token_key, (claim.token, amount));
+                }
+                n_recovered += 1;
+            }
+            Ok(true)
+        },
+    )?;
+
+    if is_done && campaign.n_outstanding_tokens == 0 {
+        campaign.status = CampaignStatus::Finalized;
+    }
+
+    campaign.updated_at = env.block.time;
+    CAMPAIGNS.save(store, &name, campaign)?;
+
+    Ok(Sweep {
+        totals,
+        events,
+        n_recovered,
+        is_done,
+    })
+}
+
+/// Send recovered totals to the given address, or over IBC to the remote
+/// depositor if the campaign was funded through IBC hooks. Transfers that fail
+/// are re-credited to the recipient so that the sweep can move on.
+fn refund_submsgs(
+    store: &mut dyn Storage,
+    env: &Env,
+    campaign: &Campaign,
+    recipient: &Addr,
+    totals: &HashMap<String, (Token, Uint128)>,
+) -> Result<Vec<SubMsg>, ContractError> {
+    let mut amounts: Vec<TokenAmount> = totals
+        .values()
+        .filter(|(_, amount)| !amount.is_zero())
+        .map(|(token, amount)| TokenAmount {
+            token: token.to_owned(),
+            amount: *amount,
+        })
+        .collect();
+    amounts.sort_by_key(|x| x.token.to_key());
+
+    if let (Some(remote), ExpiryAction::Refund) = (&campaign.remote_depositor, &campaign.expiry_action) {
+        let mut submsgs: Vec<SubMsg> = Vec::with_capacity(amounts.len());
+        for TokenAmount { token, amount } in amounts.iter() {
+            submsgs.push(remote_refund_submsg(store, env, campaign, remote, token, *amount)?);
+        }
+        Ok(submsgs)
+    } else {
+        let payouts = amounts
+            .into_iter()
+            .map(|TokenAmount { token, amount }| Payout {
+                name: campaign.name.to_owned(),
+                kind: PayoutKind::Refund,
+                recipient: recipient.to_owned(),
+                token,
+                amount,
+                fee: None,
+            })
+            .collect();
+        payout_submsgs(store, env, payouts)
+    }
+}
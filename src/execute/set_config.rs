@@ -1,12 +1,26 @@
-use crate::{error::ContractError, models::Config};
+use crate::{
+    error::ContractError,
+    models::Config,
+    state::{validate_config, CONFIG, OWNER},
+};
 use cosmwasm_std::{attr, Response};
 
 use super::Context;
 
 pub fn exec_set_config(
     ctx: Context,
-    _config: Config,
+    config: Config,
 ) -> Result<Response, ContractError> {
-    let Context { .. } = ctx;
+    let Context { deps, info, .. } = ctx;
+
+    if info.sender != OWNER.load(deps.storage)? {
+        return Err(ContractError::NotAuthorized {
+            reason: "only the owner can set config".to_owned(),
+        });
+    }
+
+    validate_config(&config)?;
+    CONFIG.save(deps.storage, &config)?;
+
     Ok(Response::new().add_attributes(vec![attr("action", "set_config")]))
 }
@@ -1,21 +1,53 @@
+use std::collections::BTreeMap;
+
 use crate::{
+    cw1155::Cw1155BatchReceiveMsg,
+    cw721::Cw721ReceiveMsg,
     error::ContractError,
-    math::{add_u128, add_u64},
-    models::ClaimRecord,
-    msg::UpsertMsg,
+    events::{claim_event, CLAIM_CREATED, CLAIM_INCREASED},
+    math::{add_u128, add_u32, add_u64, mul_ratio_u128, sub_u128},
+    models::{ClaimRecord, PendingTotal},
+    msg::{ClaimDelta, DustRecipient, ReceiveMsg, UpsertData, UpsertMsg, UpsertSharesMsg},
     state::{
-        ADDR_2_CLAIM_ID, AMOUNT_2_CLAIM_ID, CLAIM_RECORDS, ID_COUNTER, ID_LUTAB, TIME_2_CLAIM_ID, TOKEN_2_CLAIM_ID,
+        ADDR_2_CLAIM_ID, ADDR_NAME_2_CLAIM_ID, AMOUNT_2_CLAIM_ID, CAMPAIGNS, CLAIM_RECORDS, ID_COUNTER, ID_LUTAB,
+        NAME_ADDR_2_CLAIM_ID, NAME_AMOUNT_2_CLAIM_ID, PENDING_TOTALS, TIME_2_CLAIM_ID, TOKEN_2_CLAIM_ID,
     },
+    token::Token,
 };
-use cosmwasm_std::{attr, from_json, Response, Uint128};
+use cosmwasm_std::{attr, from_json, to_json_binary, Addr, Event, Response, Storage, Timestamp, Uint128, Uint64};
 use cw20::Cw20ReceiveMsg;
 
-use super::Context;
+use super::{
+    campaign::{ensure_token_allowed, is_minted, load_campaign, load_open_campaign_for_depositor, record_deposit},
+    foreign::resolve_foreign_amounts,
+    hooks::upsert_hook_submsgs,
+    ibc::validate_remote_sender,
+    Context,
+};
 
 pub fn exec_upsert_native(
     ctx: Context,
     msg: UpsertMsg,
 ) -> Result<Response, ContractError> {
+    let msg = resolve_foreign_amounts(ctx.deps.api, &ctx.env, msg)?;
+
+    // Upserts arriving through IBC hooks must be executed by the intermediate
+    // sender derived from the remote one.
+    if let Some(remote_sender) = &msg.remote_sender {
+        validate_remote_sender(&ctx.env, &ctx.info.sender, remote_sender)?;
+    }
+
+    // Claims in mint-on-claim campaigns aren't funded upfront
+    if is_minted(&load_campaign(ctx.deps.storage, &msg.name)?, &msg.token) {
+        if !ctx.info.funds.is_empty() {
+            return Err(ContractError::ValidationError {
+                reason: "upserts of minted tokens must not send funds".to_owned(),
+            });
+        }
+        let depositor = ctx.info.sender.to_owned();
+        return upsert(ctx, &depositor, msg);
+    }
+
     // Ensure that sender included the exact amount of funds in info.funds to
     // cover the sum of all individual claim amounts.
     let total_amount_required: Uint128 = msg.amounts.iter().map(|x| x.1).sum();
@@ -29,7 +61,23 @@ pub fn exec_upsert_native(
         });
     }
 
-    upsert(ctx, msg)
+    // Upserts arriving through IBC hooks must carry only the transferred coin
+    if msg.remote_sender.is_some() && ctx.info.funds.len() != 1 {
+        return Err(ContractError::ValidationError {
+            reason: "remote upserts must send exactly one coin".to_owned(),
+        });
+    }
+
+    let depositor = ctx.info.sender.to_owned();
+    upsert(ctx, &depositor, msg)
+}
+
+pub fn exec_upsert_shares_native(
+    ctx: Context,
+    msg: UpsertSharesMsg,
+) -> Result<Response, ContractError> {
+    let upsert_msg = shares_to_upsert_msg(msg, &ctx.info.sender)?;
+    exec_upsert_native(ctx, upsert_msg)
 }
 
 pub fn exec_upsert_cw20(
@@ -37,10 +85,34 @@ pub fn exec_upsert_cw20(
     msg: Cw20ReceiveMsg,
 ) -> Result<Response, ContractError> {
     let Cw20ReceiveMsg {
-        amount, msg: inner_msg, ..
+        amount,
+        msg: inner_msg,
+        sender,
     } = msg;
 
-    let upsert_msg: UpsertMsg = from_json(inner_msg.as_slice())?;
+    let depositor = ctx.deps.api.addr_validate(&sender)?;
+    let upsert_msg = match from_json(inner_msg.as_slice())? {
+        ReceiveMsg::Upsert(msg) => msg,
+        ReceiveMsg::UpsertShares(msg) => shares_to_upsert_msg(msg, &depositor)?,
+        ReceiveMsg::UpsertBatch(_) => {
+            return Err(ContractError::ValidationError {
+                reason: "batch upserts are only supported for cw1155".to_owned(),
+            });
+        },
+    };
+    let upsert_msg = resolve_foreign_amounts(ctx.deps.api, &ctx.env, upsert_msg)?;
+
+    if is_minted(&load_campaign(ctx.deps.storage, &upsert_msg.name)?, &upsert_msg.token) {
+        return Err(ContractError::ValidationError {
+            reason: "minted tokens cannot be deposited".to_owned(),
+        });
+    }
+
+    if upsert_msg.remote_sender.is_some() {
+        return Err(ContractError::ValidationError {
+            reason: "cw20 upserts cannot have a remote sender".to_owned(),
+        });
+    }
 
     // Ensure info.sender corresponds to the expected cw20 token address
     if let Some(cw20_addr) = upsert_msg.token.get_address() {
@@ -63,57 +135,361 @@ pub fn exec_upsert_cw20(
         });
     }
 
-    upsert(ctx, upsert_msg)
+    upsert(ctx, &depositor, upsert_msg)
+}
+
+pub fn exec_upsert_nft(
+    ctx: Context,
+    msg: Cw721ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let Cw721ReceiveMsg {
+        sender,
+        token_id,
+        msg: inner_msg,
+    } = msg;
+
+    let depositor = ctx.deps.api.addr_validate(&sender)?;
+    let upsert_msg = match from_json(inner_msg.as_slice())? {
+        ReceiveMsg::Upsert(msg) => resolve_foreign_amounts(ctx.deps.api, &ctx.env, msg)?,
+        ReceiveMsg::UpsertShares(_) | ReceiveMsg::UpsertBatch(_) => {
+            return Err(ContractError::ValidationError {
+                reason: "NFTs can only be upserted individually".to_owned(),
+            });
+        },
+    };
+
+    // Ensure the upserted token is the NFT received from the sending collection
+    let nft = Token::Nft {
+        collection: ctx.info.sender.to_owned(),
+        token_id,
+    };
+    if upsert_msg.token != nft {
+        return Err(ContractError::NotAuthorized {
+            reason: "Upserted token does not match the NFT received".to_owned(),
+        });
+    }
+
+    if upsert_msg.remote_sender.is_some() {
+        return Err(ContractError::ValidationError {
+            reason: "NFT upserts cannot have a remote sender".to_owned(),
+        });
+    }
+
+    if upsert_msg.amounts.len() != 1 || upsert_msg.amounts[0].1 != Uint128::one() {
+        return Err(ContractError::ValidationError {
+            reason: "an NFT must be upserted to a single recipient with an amount of 1".to_owned(),
+        });
+    }
+
+    // A second record for the same NFT would take its amount to 2, which can
+    // never be transferred.
+    let recipient = &upsert_msg.amounts[0].0;
+    if let Some(id) = ID_LUTAB.may_load(ctx.deps.storage, (&upsert_msg.name, &nft.to_key(), recipient))? {
+        if CLAIM_RECORDS.has(ctx.deps.storage, id.u64()) {
+            return Err(ContractError::ValidationError {
+                reason: "the recipient already has a pending claim for this NFT".to_owned(),
+            });
+        }
+    }
+
+    upsert(ctx, &depositor, upsert_msg)
+}
+
+pub fn exec_upsert_cw1155(
+    ctx: Context,
+    msg: Cw1155BatchReceiveMsg,
+) -> Result<Response, ContractError> {
+    let Context { mut deps, env, info } = ctx;
+    let Cw1155BatchReceiveMsg {
+        operator,
+        from,
+        batch,
+        msg: inner_msg,
+    } = msg;
+
+    // Tokens minted straight into the contract have no previous owner
+    let depositor = deps.api.addr_validate(&from.unwrap_or(operator))?;
+    let upsert_msgs = match from_json(inner_msg.as_slice())? {
+        ReceiveMsg::Upsert(msg) => vec![msg],
+        ReceiveMsg::UpsertShares(msg) => vec![shares_to_upsert_msg(msg, &depositor)?],
+        ReceiveMsg::UpsertBatch(msgs) => msgs,
+    };
+
+    // Ensure upserted tokens come from the sending contract and that their
+    // totals match the amounts received for each token ID exactly.
+    let mut received: BTreeMap<String, Uint128> = BTreeMap::new();
+    for (token_id, amount) in batch.into_iter() {
+        let total = received.entry(token_id).or_default();
+        *total = add_u128(*total, amount)?;
+    }
+
+    let mut upserted: BTreeMap<String, Uint128> = BTreeMap::new();
+    let mut resolved_msgs: Vec<UpsertMsg> = Vec::with_capacity(upsert_msgs.len());
+    for msg in upsert_msgs.into_iter() {
+        let msg = resolve_foreign_amounts(deps.api, &env, msg)?;
+        let token_id = match &msg.token {
+            Token::MultiToken { contract, token_id } if *contract == info.sender => token_id.to_owned(),
+            _ => {
+                return Err(ContractError::NotAuthorized {
+                    reason: "Upserted token is not from the sending cw1155 contract".to_owned(),
+                });
+            },
+        };
+        if msg.remote_sender.is_some() {
+            return Err(ContractError::ValidationError {
+                reason: "cw1155 upserts cannot have a remote sender".to_owned(),
+            });
+        }
+        if is_minted(&load_campaign(deps.storage, &msg.name)?, &msg.token) {
+            return Err(ContractError::ValidationError {
+                reason: "minted tokens cannot be deposited".to_owned(),
+            });
+        }
+        let total = upserted.entry(token_id).or_default();
+        for (_, amount) in msg.amounts.iter() {
+            *total = add_u128(*total, *amount)?;
+        }
+        resolved_msgs.push(msg);
+    }
+
+    if received != upserted {
+        return Err(ContractError::InsufficientFunds {
+            reason: "received cw1155 amounts do not match upserted claim amounts".to_owned(),
+        });
+    }
+
+    // Merge the responses of each upsert into one
+    let mut resp = Response::new();
+    let mut claims: Vec<(Uint64, Addr, Uint128)> = Vec::with_capacity(8);
+    for msg in resolved_msgs.into_iter() {
+        let ctx = Context {
+            deps: deps.branch(),
+            env: env.clone(),
+            info: info.clone(),
+        };
+        let upsert_resp = upsert(ctx, &depositor, msg)?;
+        if let Some(data) = &upsert_resp.data {
+            claims.extend(from_json::<UpsertData>(data)?.claims);
+        }
+        resp = resp
+            .add_attributes(upsert_resp.attributes)
+            .add_events(upsert_resp.events)
+            .add_submessages(upsert_resp.messages);
+    }
+
+    Ok(resp.set_data(to_json_binary(&UpsertData { claims })?))
 }
 
 pub fn upsert(
     ctx: Context,
+    depositor: &Addr,
     msg: UpsertMsg,
 ) -> Result<Response, ContractError> {
     let Context { deps, env, .. } = ctx;
-    let UpsertMsg { name, token, amounts } = msg;
+    let UpsertMsg {
+        name,
+        token,
+        amounts,
+        callback,
+        remote_sender,
+        ..
+    } = msg;
+
+    let mut campaign = load_open_campaign_for_depositor(deps.storage, &name, depositor, remote_sender.as_ref())?;
+    ensure_token_allowed(deps.storage, &name, &token)?;
+
+    if let Some(callback) = callback {
+        campaign.callback = Some(deps.api.addr_validate(callback.as_str())?);
+    }
+
+    let mut events: Vec<Event> = Vec::with_capacity(amounts.len());
+    let mut upserted: Vec<(Uint64, Addr, Uint128)> = Vec::with_capacity(amounts.len());
+    let mut deltas: Vec<ClaimDelta> = Vec::with_capacity(amounts.len());
 
     for (recipient, amount) in amounts {
-        let key = (&name, &token.to_key(), &recipient);
+        let (record, is_new) = credit_claim(deps.storage, env.block.time, &name, &token, &recipient, amount)?;
+
+        record_deposit(deps.storage, &mut campaign, &token, amount)?;
+
+        events.push(claim_event(
+            if is_new { CLAIM_CREATED } else { CLAIM_INCREASED },
+            &record,
+            amount,
+            record.amount,
+        ));
+        upserted.push((record.id, recipient.to_owned(), record.amount));
+        deltas.push(ClaimDelta {
+            claim: record,
+            delta: amount,
+        });
+    }
+
+    campaign.updated_at = env.block.time;
+    CAMPAIGNS.save(deps.storage, &name, &campaign)?;
+
+    let hook_submsgs = upsert_hook_submsgs(deps.storage, &name, depositor, &deltas)?;
 
-        // Get or create claim ID
-        let id = if let Some(id) = ID_LUTAB.may_load(deps.storage, key)? {
-            id
+    Ok(Response::new()
+        .add_attributes(vec![attr("action", "upsert"), attr("name", name)])
+        .set_data(to_json_binary(&UpsertData { claims: upserted })?)
+        .add_events(events)
+        .add_submessages(hook_submsgs))
+}
+
+/// Add an amount to the recipient's claim record for the given campaign and
+/// token, creating the record if it doesn't exist, and update its indexes and
+/// the recipient's pending total. Returns the record and whether it's new.
+pub fn credit_claim(
+    store: &mut dyn Storage,
+    time: Timestamp,
+    name: &String,
+    token: &Token,
+    recipient: &Addr,
+    amount: Uint128,
+) -> Result<(ClaimRecord, bool), ContractError> {
+    // Zero-amount records can't be paid out, since zero-value transfers fail
+    if amount.is_zero() {
+        return Err(ContractError::ValidationError {
+            reason: "claim amounts must be greater than zero".to_owned(),
+        });
+    }
+
+    let key = (name, &token.to_key(), recipient);
+
+    // Get or create claim ID
+    let id = if let Some(id) = ID_LUTAB.may_load(store, key)? {
+        id
+    } else {
+        let id = ID_COUNTER.update(store, |n| -> Result<_, ContractError> { add_u64(n, 1u64) })?;
+        ID_LUTAB.save(store, key, &id)?;
+        id
+    };
+
+    // Copy previous claim record, update it in storage, and return it.
+    let mut prev_record: Option<ClaimRecord> = None;
+    let record = CLAIM_RECORDS.update(store, id.u64(), |maybe_record| -> Result<_, ContractError> {
+        if let Some(mut record) = maybe_record {
+            prev_record = Some(record.clone());
+            record.amount = add_u128(record.amount, amount)?;
+            record.updated_at = time;
+            Ok(record)
         } else {
-            let id = ID_COUNTER.update(deps.storage, |n| -> Result<_, ContractError> { add_u64(n, 1u64) })?;
-            ID_LUTAB.save(deps.storage, key, &id)?;
-            id
-        };
+            Ok(ClaimRecord {
+                id,
+                recipient: recipient.to_owned(),
+                name: name.to_owned(),
+                updated_at: time,
+                token: token.to_owned(),
+                amount,
+            })
+        }
+    })?;
 
-        // Copy previous claim record, update it in storage, and return it.
-        let mut prev_record: Option<ClaimRecord> = None;
-        let record = CLAIM_RECORDS.update(deps.storage, id.u64(), |maybe_record| -> Result<_, ContractError> {
-            if let Some(mut record) = maybe_record {
-                prev_record = Some(record.clone());
-                record.amount = add_u128(record.amount, amount)?;
-                record.updated_at = env.block.time;
-                Ok(record)
-            } else {
-                Ok(ClaimRecord {
-                    name: name.to_owned(),
-                    updated_at: env.block.time,
-                    token: token.to_owned(),
-                    amount,
-                })
+    // Update lookup tables for paginating a recipient's claims by updated
+    // time and amount.
+    if let Some(prev_record) = &prev_record {
+        TIME_2_CLAIM_ID.remove(store, (recipient, prev_record.updated_at.nanos(), id.into()));
+        AMOUNT_2_CLAIM_ID.remove(store, (recipient, prev_record.amount.u128(), id.into()));
+        NAME_AMOUNT_2_CLAIM_ID.remove(store, (name, prev_record.amount.u128(), id.into()));
+    }
+    TIME_2_CLAIM_ID.save(store, (recipient, record.updated_at.nanos(), id.into()), &())?;
+    AMOUNT_2_CLAIM_ID.save(store, (recipient, record.amount.u128(), id.into()), &())?;
+    TOKEN_2_CLAIM_ID.save(store, (recipient, &token.to_key(), id.into()), &())?;
+    ADDR_2_CLAIM_ID.save(store, (recipient, id.into()), &())?;
+    ADDR_NAME_2_CLAIM_ID.save(store, (recipient, name, id.into()), &())?;
+
+    // Update lookup tables for paginating a campaign's claims by amount
+    // and recipient.
+    NAME_AMOUNT_2_CLAIM_ID.save(store, (name, record.amount.u128(), id.into()), &())?;
+    NAME_ADDR_2_CLAIM_ID.save(store, (name, recipient, id.into()), &())?;
+
+    // Update recipient's pending total for the token
+    PENDING_TOTALS.update(
+        store,
+        (recipient, &token.to_key()),
+        |maybe_total| -> Result<_, ContractError> {
+            let mut total = maybe_total.unwrap_or_else(|| PendingTotal {
+                token: token.to_owned(),
+                amount: Uint128::zero(),
+                count: 0,
+            });
+            total.amount = add_u128(total.amount, amount)?;
+            if prev_record.is_none() {
+                total.count = add_u32(total.count, 1)?;
             }
-        })?;
-
-        // Update lookup tables for paginating a recipient's claims by updated
-        // time and amount.
-        if let Some(prev_record) = prev_record {
-            TIME_2_CLAIM_ID.remove(deps.storage, (&recipient, prev_record.updated_at.nanos(), id.into()));
-            TIME_2_CLAIM_ID.save(deps.storage, (&recipient, record.updated_at.nanos(), id.into()), &())?;
-            AMOUNT_2_CLAIM_ID.remove(deps.storage, (&recipient, prev_record.amount.u128(), id.into()));
-            AMOUNT_2_CLAIM_ID.save(deps.storage, (&recipient, record.amount.u128(), id.into()), &())?;
-            TOKEN_2_CLAIM_ID.save(deps.storage, (&recipient, &token.to_key(), id.into()), &())?;
-            ADDR_2_CLAIM_ID.save(deps.storage, (&recipient, id.into()), &())?;
+            Ok(total)
+        },
+    )?;
+
+    Ok((record, prev_record.is_none()))
+}
+
+/// Convert shares into the amounts owed to each recipient, assigning rounding
+/// dust to the depositor or largest holder so that amounts sum to the total.
+fn shares_to_upsert_msg(
+    msg: UpsertSharesMsg,
+    depositor: &Addr,
+) -> Result<UpsertMsg, ContractError> {
+    let UpsertSharesMsg {
+        name,
+        token,
+        total,
+        shares,
+        dust_recipient,
+        callback,
+    } = msg;
+
+    let mut total_shares = Uint128::zero();
+    for (_, n) in shares.iter() {
+        total_shares = add_u128(total_shares, *n)?;
+    }
+
+    if total_shares.is_zero() {
+        return Err(ContractError::ValidationError {
+            reason: "total shares must be greater than zero".to_owned(),
+        });
+    }
+
+    let mut amounts: Vec<(Addr, Uint128)> = Vec::with_capacity(shares.len() + 1);
+    let mut amount_assigned = Uint128::zero();
+    for (recipient, n) in shares.iter() {
+        let amount = mul_ratio_u128(total, *n, total_shares)?;
+        amount_assigned = add_u128(amount_assigned, amount)?;
+        amounts.push((recipient.to_owned(), amount));
+    }
+
+    let dust = sub_u128(total, amount_assigned)?;
+    if !dust.is_zero() {
+        match dust_recipient {
+            DustRecipient::Depositor => amounts.push((depositor.to_owned(), dust)),
+            DustRecipient::LargestHolder => {
+                // Take the first recipient with the max number of shares
+                let mut i_max = 0;
+                for (i, (_, n)) in shares.iter().enumerate() {
+                    if *n > shares[i_max].1 {
+                        i_max = i;
+                    }
+                }
+                amounts[i_max].1 = add_u128(amounts[i_max].1, dust)?;
+            },
         }
     }
 
-    Ok(Response::new().add_attributes(vec![attr("action", "upsert"), attr("name", name)]))
+    if amounts.iter().map(|x| x.1).sum::<Uint128>() != total {
+        return Err(ContractError::ValidationError {
+            reason: "pro-rata amounts do not sum to the deposited total".to_owned(),
+        });
+    }
+
+    // Don't create empty claims for recipients with negligible shares
+    amounts.retain(|x| !x.1.is_zero());
+
+    Ok(UpsertMsg {
+        name,
+        token,
+        amounts,
+        callback,
+        remote_sender: None,
+        foreign_amounts: None,
+    })
 }
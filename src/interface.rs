@@ -22,7 +22,9 @@ impl<Chain> Uploadable for Contract<Chain> {
                 crate::contract::instantiate,
                 crate::contract::query,
             )
-            .with_migrate(crate::contract::migrate),
+            .with_migrate(crate::contract::migrate)
+            .with_sudo(crate::contract::sudo)
+            .with_reply(crate::contract::reply),
         )
     }
 }
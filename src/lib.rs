@@ -1,11 +1,16 @@
 #[cfg(not(feature = "library"))]
 pub mod contract;
+pub mod cw1155;
+pub mod cw721;
 pub mod error;
+pub mod events;
 #[cfg(not(feature = "library"))]
 pub mod execute;
+#[allow(dead_code)]
 mod math;
 pub mod models;
 pub mod msg;
+mod proto;
 #[cfg(not(feature = "library"))]
 pub mod query;
 pub mod state;
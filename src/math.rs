@@ -116,3 +116,14 @@ pub fn add_u32(
         })
     })
 }
+
+pub fn sub_u32(
+    a: u32,
+    b: u32,
+) -> Result<u32, ContractError> {
+    a.checked_sub(b).ok_or_else(|| {
+        ContractError::Std(StdError::Overflow {
+            source: OverflowError::new(OverflowOperation::Sub, a, b),
+        })
+    })
+}
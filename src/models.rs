@@ -1,15 +1,169 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Timestamp, Uint128};
+use cosmwasm_std::{Addr, Timestamp, Uint128, Uint64};
 
-use crate::token::Token;
+use crate::token::{Token, TokenFactory};
 
 #[cw_serde]
-pub struct Config {}
+pub struct Config {
+    /// If set, only this address may execute `Distribute`. Otherwise, the
+    /// distribution crank is permissionless.
+    pub distributor: Option<Addr>,
+    /// Fee paid to the caller of `Distribute` out of each payout, expressed in
+    /// parts per million.
+    pub keeper_fee_pct: Option<Uint128>,
+    /// Tokenfactory module used to mint and burn native denoms, if any.
+    pub tokenfactory: Option<TokenFactory>,
+}
 
 #[cw_serde]
 pub struct ClaimRecord {
+    pub id: Uint64,
+    pub recipient: Addr,
     pub updated_at: Timestamp,
     pub name: String,
     pub token: Token,
     pub amount: Uint128,
 }
+
+/// Running total of what an address has pending for a single token.
+#[cw_serde]
+pub struct PendingTotal {
+    pub token: Token,
+    pub amount: Uint128,
+    pub count: u32,
+}
+
+/// Receipt of a claim record being paid out, kept after the record is deleted.
+#[cw_serde]
+pub struct ClaimHistoryEntry {
+    pub id: Uint64,
+    pub name: String,
+    pub token: Token,
+    pub amount: Uint128,
+    pub recipient: Addr,
+    pub claimed_at: Timestamp,
+    pub height: u64,
+    /// Whether this entry undoes an earlier receipt of the amount, which was
+    /// returned and re-credited to the recipient as a claim.
+    pub is_reversal: bool,
+}
+
+#[cw_serde]
+pub enum CampaignStatus {
+    /// Accepting new and increased claims.
+    Open,
+    /// No longer accepting upserts, but existing claims can still be claimed.
+    Closed,
+    /// Closed with nothing left outstanding.
+    Finalized,
+}
+
+#[cw_serde]
+pub struct CampaignTokenTotals {
+    pub token: Token,
+    pub deposited: Uint128,
+    pub claimed: Uint128,
+    pub recovered: Uint128,
+    pub outstanding: Uint128,
+    /// Amount of a minted token already minted for claims whose payouts were
+    /// returned, which later claims are paid out of before minting more.
+    pub returned: Uint128,
+}
+
+/// Address on a remote chain that deposits into a campaign through IBC hooks.
+#[cw_serde]
+pub struct RemoteSender {
+    /// Channel on this chain over which the sender's transfers arrive.
+    pub channel_id: String,
+    pub address: String,
+}
+
+/// What happens to whatever remains unclaimed once a campaign's claim window
+/// has ended.
+#[cw_serde]
+pub enum ExpiryAction {
+    /// Return it to the campaign's creator.
+    Refund,
+    /// Burn it, including native denoms through the tokenfactory module, which
+    /// must have been created by this contract.
+    Burn,
+    /// Send it to the given address.
+    SendTo(Addr),
+}
+
+/// Token minted to recipients as they claim, rather than deposited upfront.
+#[cw_serde]
+pub struct MintSettings {
+    pub token: Token,
+    /// Max total amount that can be upserted, and therefore minted.
+    pub cap: Uint128,
+}
+
+/// Claimed or refunded funds in flight over IBC, re-credited to the local
+/// account's records if the transfer times out or fails.
+#[cw_serde]
+pub struct IbcTransfer {
+    pub kind: PayoutKind,
+    /// Local account credited if the transfer fails: the claimant, or the
+    /// creator of a campaign being refunded.
+    pub claimant: Addr,
+    pub channel_id: String,
+    pub token: Token,
+    pub amount: Uint128,
+    /// Campaign name and amount of each claim record contributing to the total.
+    pub claims: Vec<(String, Uint128)>,
+}
+
+/// What a payout out of the contract was for, which determines how its amount
+/// is re-credited if the transfer fails.
+#[cw_serde]
+pub enum PayoutKind {
+    /// Claim paid to its recipient.
+    Claim,
+    /// Keeper fee deducted from claims paid by a crank.
+    KeeperFee,
+    /// Unclaimed funds swept out of an expired campaign.
+    Refund,
+}
+
+/// Transfer awaiting its reply, re-credited as a claim to its recipient in the
+/// campaign if it fails, so that one unpayable record can't block a crank.
+#[cw_serde]
+pub struct Payout {
+    pub name: String,
+    pub kind: PayoutKind,
+    pub recipient: Addr,
+    pub token: Token,
+    pub amount: Uint128,
+    /// Keeper and the fee taken out of a claim, paid only once the claim's
+    /// own transfer succeeds.
+    pub fee: Option<(Addr, Uint128)>,
+}
+
+#[cw_serde]
+pub struct Campaign {
+    pub name: String,
+    pub creator: Addr,
+    pub title: String,
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+    pub created_at: Timestamp,
+    pub updated_at: Timestamp,
+    pub status: CampaignStatus,
+    /// Number of tokens with an amount still outstanding. Totals for each
+    /// token are stored separately, as there can be one per NFT.
+    pub n_outstanding_tokens: u32,
+    /// Claims can't be claimed before this time, if set.
+    pub claim_start: Option<Timestamp>,
+    /// Claims can't be claimed from this time onward, if set, at which point
+    /// the creator can recover whatever remains unclaimed.
+    pub claim_end: Option<Timestamp>,
+    /// Contract notified whenever a claim in the campaign is paid, if set.
+    pub callback: Option<Addr>,
+    /// Remote chain address registered by the creator to deposit into the
+    /// campaign via IBC hooks, to which recovered funds are returned over IBC.
+    pub remote_depositor: Option<RemoteSender>,
+    /// If set, the campaign mints this token on claim.
+    pub mint: Option<MintSettings>,
+    pub expiry_action: ExpiryAction,
+}
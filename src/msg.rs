@@ -1,18 +1,59 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Addr, Uint128, Uint64};
+use cosmwasm_std::{Addr, Binary, Timestamp, Uint128, Uint64};
+use cw20::Cw20ReceiveMsg;
 
 use crate::{
-    models::{ClaimRecord, Config},
-    token::Token,
+    cw1155::Cw1155BatchReceiveMsg,
+    cw721::Cw721ReceiveMsg,
+    models::{
+        Campaign, CampaignTokenTotals, ClaimHistoryEntry, ClaimRecord, Config, ExpiryAction, MintSettings,
+        PendingTotal, RemoteSender,
+    },
+    token::{Token, TokenAmount, TokenSource},
 };
 
 #[cw_serde]
-pub struct InstantiateMsg {}
+pub struct InstantiateMsg {
+    pub config: Config,
+}
 
 #[cw_serde]
 #[derive(cw_orch::ExecuteFns)]
 pub enum ExecuteMsg {
     SetConfig(Config),
+    #[cw_orch(payable)]
+    Upsert(UpsertMsg),
+    #[cw_orch(payable)]
+    UpsertShares(UpsertSharesMsg),
+    Receive(Cw20ReceiveMsg),
+    ReceiveNft(Cw721ReceiveMsg),
+    BatchReceive(Cw1155BatchReceiveMsg),
+    Claim(ClaimMsg),
+    Distribute {
+        name: String,
+        limit: Option<u32>,
+    },
+    CreateCampaign(CreateCampaignMsg),
+    UpdateCampaign(UpdateCampaignMsg),
+    CloseCampaign {
+        name: String,
+    },
+    Recover {
+        name: String,
+        limit: Option<u32>,
+    },
+    FinalizeExpired {
+        name: String,
+        limit: Option<u32>,
+    },
+    AddHook {
+        address: Addr,
+        name: Option<String>,
+    },
+    RemoveHook {
+        address: Addr,
+        name: Option<String>,
+    },
 }
 
 #[cw_serde]
@@ -20,6 +61,62 @@ pub enum ExecuteMsg {
 pub enum QueryMsg {
     #[returns(ConfigResponse)]
     Config {},
+
+    #[returns(CampaignResponse)]
+    Campaign { name: String },
+
+    #[returns(CampaignsResponse)]
+    Campaigns { cursor: Option<String> },
+
+    #[returns(ClaimHistoryResponse)]
+    ClaimHistory {
+        scope: ClaimHistoryScope,
+        cursor: Option<Uint64>,
+    },
+
+    #[returns(ClaimedTotalsResponse)]
+    ClaimedTotals { address: Addr },
+
+    #[returns(ClaimableSummaryResponse)]
+    ClaimableSummary { address: Addr },
+
+    #[returns(ClaimsResponse)]
+    Claims(ClaimsQueryMsg),
+
+    #[returns(ClaimResponse)]
+    Claim { id: Uint64 },
+
+    #[returns(ClaimResponse)]
+    ClaimByKey {
+        name: String,
+        token: Token,
+        recipient: Addr,
+    },
+
+    #[returns(ClaimsByIdsResponse)]
+    ClaimsByIds { ids: Vec<Uint64> },
+
+    #[returns(CampaignClaimsResponse)]
+    ClaimsByCampaign(CampaignClaimsQueryMsg),
+
+    #[returns(HooksResponse)]
+    Hooks { name: Option<String> },
+
+    #[returns(TokenSourcesResponse)]
+    TokenSources { name: String },
+
+    #[returns(TokenTotalsResponse)]
+    TokenTotals { name: String, cursor: Option<String> },
+
+    #[returns(SimulateClaimResponse)]
+    SimulateClaim {
+        address: Addr,
+        ids: Option<Vec<Uint64>>,
+        recipient: Option<Addr>,
+        filter: Option<ClaimFilter>,
+        cursor: Option<Uint64>,
+        amount: Option<Uint128>,
+    },
 }
 
 #[cw_serde]
@@ -28,16 +125,204 @@ pub struct MigrateMsg {}
 #[cw_serde]
 pub struct ConfigResponse(pub Config);
 
+#[cw_serde]
+pub struct CampaignResponse(pub Campaign);
+
+#[cw_serde]
+pub struct CampaignsResponse {
+    pub campaigns: Vec<Campaign>,
+    pub cursor: Option<String>,
+}
+
+#[cw_serde]
+pub struct TokenTotalsResponse {
+    pub totals: Vec<CampaignTokenTotals>,
+    pub cursor: Option<String>,
+}
+
+#[cw_serde]
+pub struct TokenSourcesResponse {
+    pub sources: Vec<TokenSource>,
+}
+
+#[cw_serde]
+pub struct CreateCampaignMsg {
+    pub name: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+    pub claim_start: Option<Timestamp>,
+    pub claim_end: Option<Timestamp>,
+    /// Denoms and token contracts that the campaign accepts deposits of. The
+    /// token of a mint-on-claim campaign is accepted implicitly.
+    pub tokens: Vec<TokenSource>,
+    /// Mint the token on claim instead of holding deposits, for tokens the
+    /// contract has minter rights over.
+    pub mint: Option<MintSettings>,
+    /// What to do with unclaimed funds once the claim window ends. Defaults to
+    /// refunding the creator.
+    pub expiry_action: Option<ExpiryAction>,
+    /// Remote chain address allowed to upsert into and recover from the
+    /// campaign through IBC hooks, on behalf of the creator.
+    pub remote_depositor: Option<RemoteSender>,
+}
+
+#[cw_serde]
+pub struct UpdateCampaignMsg {
+    pub name: String,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+    /// Extend the claim window. Can't be earlier than the existing end time.
+    pub claim_end: Option<Timestamp>,
+    /// Accept deposits of additional denoms and token contracts.
+    pub add_tokens: Option<Vec<TokenSource>>,
+    /// Replace the remote chain address allowed to act on the campaign through
+    /// IBC hooks.
+    pub remote_depositor: Option<RemoteSender>,
+}
+
 #[cw_serde]
 pub struct UpsertMsg {
     pub name: String,
     pub token: Token,
     pub amounts: Vec<(Addr, Uint128)>,
+    /// Contract to notify whenever a claim in the campaign is paid, replacing
+    /// any callback previously registered.
+    pub callback: Option<Addr>,
+    /// Original sender of an ICS20 transfer that executes this upsert via IBC
+    /// hooks, which must correspond to the local sender of the upsert and be
+    /// the campaign's remote depositor.
+    pub remote_sender: Option<RemoteSender>,
+    /// Amounts owed to recipients known only by their public key or address
+    /// on another chain, added to `amounts` under their local address.
+    pub foreign_amounts: Option<Vec<(ForeignRecipient, Uint128)>>,
+}
+
+/// Recipient identified by something other than a local address.
+#[cw_serde]
+pub enum ForeignRecipient {
+    /// Compressed secp256k1 public key. Claims go to the local address derived
+    /// from the key, and may also be claimed by any address with a `KeyProof`.
+    PubKey(Binary),
+    /// Bech32 address on another chain, claimable by the local address with
+    /// the same canonical bytes.
+    Bech32(String),
+}
+
+/// Proof that the claimant controls a public key, entitling it to the claims
+/// of the key's derived local address.
+#[cw_serde]
+pub struct KeyProof {
+    pub pubkey: Binary,
+    /// Signature over sha256(contract address | claimant address).
+    pub signature: Binary,
+}
+
+/// Data returned by an upsert.
+#[cw_serde]
+pub struct UpsertData {
+    /// ID, recipient and new amount of each claim record upserted.
+    pub claims: Vec<(Uint64, Addr, Uint128)>,
+}
+
+/// Split a total deposit among recipients in proportion to their shares.
+#[cw_serde]
+pub struct UpsertSharesMsg {
+    pub name: String,
+    pub token: Token,
+    pub total: Uint128,
+    pub shares: Vec<(Addr, Uint128)>,
+    pub dust_recipient: DustRecipient,
+    pub callback: Option<Addr>,
+}
+
+/// Who receives whatever is left over from rounding down pro-rata amounts.
+#[cw_serde]
+pub enum DustRecipient {
+    Depositor,
+    /// The recipient with the most shares, or the first listed in case of a tie.
+    LargestHolder,
+}
+
+/// Message expected in the `msg` field of a cw20 `Send`, cw721 `SendNft` or
+/// cw1155 `BatchSendFrom` to this contract. NFTs can only be upserted, to a
+/// single recipient.
+#[cw_serde]
+pub enum ReceiveMsg {
+    Upsert(UpsertMsg),
+    UpsertShares(UpsertSharesMsg),
+    /// Upsert each cw1155 token ID received in a batch. Only valid for cw1155.
+    UpsertBatch(Vec<UpsertMsg>),
 }
 
 #[cw_serde]
 pub struct ClaimMsg {
     pub ids: Option<Vec<Uint64>>,
+    /// Address to send claimed funds to, if not the claimant.
+    pub recipient: Option<Addr>,
+    /// Only claim records matching the filter.
+    pub filter: Option<ClaimFilter>,
+    /// When claiming everything, resume after this claim ID, as returned by
+    /// the previous claim.
+    pub cursor: Option<Uint64>,
+    /// Claim only this amount from the single record given in `ids`, leaving
+    /// the remainder pending.
+    pub amount: Option<Uint128>,
+    /// Send claimed native funds over IBC instead of to a local recipient.
+    pub ibc: Option<IbcDestination>,
+    /// Claim the records of the address derived from a public key that the
+    /// sender has proved control of.
+    pub proof: Option<KeyProof>,
+}
+
+#[cw_serde]
+pub struct IbcDestination {
+    pub channel_id: String,
+    pub remote_address: String,
+    /// Seconds from now after which the transfer times out and claimed funds
+    /// are re-credited. Defaults to 10 minutes.
+    pub timeout_seconds: Option<u64>,
+}
+
+/// Messages sent by the chain's ibc-hooks module to report the outcome of
+/// transfers sent with an `ibc_callback` memo.
+#[cw_serde]
+pub enum SudoMsg {
+    IbcLifecycleComplete(IbcLifecycleComplete),
+}
+
+#[cw_serde]
+pub enum IbcLifecycleComplete {
+    IbcAck {
+        channel: String,
+        sequence: u64,
+        ack: String,
+        success: bool,
+    },
+    IbcTimeout {
+        channel: String,
+        sequence: u64,
+    },
+}
+
+/// Data returned by a claim.
+#[cw_serde]
+pub struct ClaimData {
+    /// Total amount paid out for each token.
+    pub payouts: Vec<TokenAmount>,
+    /// Whether more records remain to be claimed with the same filter.
+    pub has_more: bool,
+    /// Cursor to pass into the next claim to continue where this one stopped.
+    pub cursor: Option<Uint64>,
+}
+
+/// Criteria that claim records must all meet in order to be claimed.
+#[cw_serde]
+pub struct ClaimFilter {
+    pub tokens: Option<Vec<Token>>,
+    pub names: Option<Vec<String>>,
+    pub min_amount: Option<Uint128>,
 }
 
 #[cw_serde]
@@ -52,6 +337,7 @@ pub enum OrderKeyCursor {
     UpdatedAt((Uint64, Uint64)),
     Amount((Uint128, Uint64)),
     Token((String, Uint64)),
+    Id(Uint64),
 }
 
 #[cw_serde]
@@ -59,6 +345,11 @@ pub struct ClaimsQueryMsg {
     pub address: Addr,
     pub order_by: OrderKey,
     pub cursor: Option<OrderKeyCursor>,
+    /// Only include claims for this token. When filtering by token or name,
+    /// claims are ordered by descending ID rather than `order_by`.
+    pub token: Option<Token>,
+    /// Only include claims in this campaign.
+    pub name: Option<String>,
 }
 
 #[cw_serde]
@@ -66,3 +357,122 @@ pub struct ClaimsResponse {
     pub claims: Vec<ClaimRecord>,
     pub cursor: Option<OrderKeyCursor>,
 }
+
+#[cw_serde]
+pub enum ClaimHistoryScope {
+    Address(Addr),
+    Campaign(String),
+}
+
+#[cw_serde]
+pub struct ClaimHistoryResponse {
+    pub entries: Vec<ClaimHistoryEntry>,
+    pub cursor: Option<Uint64>,
+}
+
+#[cw_serde]
+pub struct ClaimedTotalsResponse {
+    pub totals: Vec<TokenAmount>,
+}
+
+#[cw_serde]
+pub struct ClaimableSummaryResponse {
+    pub totals: Vec<PendingTotal>,
+}
+
+#[cw_serde]
+pub struct SimulateClaimResponse {
+    pub recipient: Addr,
+    pub payouts: Vec<TokenAmount>,
+    pub ids: Vec<Uint64>,
+    pub has_more: bool,
+    pub cursor: Option<Uint64>,
+}
+
+#[cw_serde]
+pub struct ClaimResponse(pub Option<ClaimRecord>);
+
+#[cw_serde]
+pub struct ClaimsByIdsResponse {
+    pub claims: Vec<ClaimRecord>,
+}
+
+#[cw_serde]
+pub enum CampaignOrderKey {
+    Amount,
+    Recipient,
+}
+
+#[cw_serde]
+pub enum CampaignOrderKeyCursor {
+    Amount((Uint128, Uint64)),
+    Recipient((Addr, Uint64)),
+}
+
+#[cw_serde]
+pub struct CampaignClaimsQueryMsg {
+    pub name: String,
+    pub order_by: CampaignOrderKey,
+    pub cursor: Option<CampaignOrderKeyCursor>,
+}
+
+#[cw_serde]
+pub struct CampaignClaimsResponse {
+    pub claims: Vec<ClaimRecord>,
+    pub cursor: Option<CampaignOrderKeyCursor>,
+}
+
+#[cw_serde]
+pub struct HooksResponse {
+    pub hooks: Vec<Addr>,
+}
+
+/// Change to a claim record, where the record's amount is its new balance.
+#[cw_serde]
+pub struct ClaimDelta {
+    pub claim: ClaimRecord,
+    pub delta: Uint128,
+}
+
+/// Sent to hooks whenever claims are paid out.
+#[cw_serde]
+pub struct ClaimHookMsg {
+    pub claims: Vec<ClaimDelta>,
+}
+
+/// Sent to hooks whenever claims are created or increased.
+#[cw_serde]
+pub struct UpsertHookMsg {
+    pub name: String,
+    pub depositor: Addr,
+    pub claims: Vec<ClaimDelta>,
+}
+
+/// Message executed on hook contracts.
+#[cw_serde]
+pub enum HookExecuteMsg {
+    ClaimHook(ClaimHookMsg),
+    UpsertHook(UpsertHookMsg),
+}
+
+/// Amount paid out of a claim record to its recipient.
+#[cw_serde]
+pub struct ClaimPayout {
+    pub recipient: Addr,
+    pub token: Token,
+    pub amount: Uint128,
+}
+
+/// Sent to a campaign's depositor callback once per tx in which any of its
+/// claims are paid.
+#[cw_serde]
+pub struct ClaimPaidMsg {
+    pub name: String,
+    pub payouts: Vec<ClaimPayout>,
+}
+
+/// Message executed on depositor callback contracts.
+#[cw_serde]
+pub enum CallbackExecuteMsg {
+    ClaimPaid(ClaimPaidMsg),
+}
@@ -0,0 +1,73 @@
+//! Minimal protobuf encoding for the few stargate messages the contract sends.
+
+/// Protobuf message writer. Fields with default values are omitted, as per
+/// proto3 semantics.
+#[derive(Default)]
+pub struct ProtoBuf {
+    buf: Vec<u8>,
+}
+
+impl ProtoBuf {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn string(
+        mut self,
+        field: u32,
+        value: &str,
+    ) -> Self {
+        if !value.is_empty() {
+            self.write_key(field, 2);
+            self.write_varint(value.len() as u64);
+            self.buf.extend_from_slice(value.as_bytes());
+        }
+        self
+    }
+
+    pub fn uint64(
+        mut self,
+        field: u32,
+        value: u64,
+    ) -> Self {
+        if value != 0 {
+            self.write_key(field, 0);
+            self.write_varint(value);
+        }
+        self
+    }
+
+    pub fn message(
+        mut self,
+        field: u32,
+        value: ProtoBuf,
+    ) -> Self {
+        self.write_key(field, 2);
+        self.write_varint(value.buf.len() as u64);
+        self.buf.extend(value.buf);
+        self
+    }
+
+    pub fn into_vec(self) -> Vec<u8> {
+        self.buf
+    }
+
+    fn write_key(
+        &mut self,
+        field: u32,
+        wire_type: u8,
+    ) {
+        self.write_varint(((field as u64) << 3) | wire_type as u64);
+    }
+
+    fn write_varint(
+        &mut self,
+        mut value: u64,
+    ) {
+        while value >= 0x80 {
+            self.buf.push((value as u8) | 0x80);
+            value >>= 7;
+        }
+        self.buf.push(value as u8);
+    }
+}
@@ -0,0 +1,101 @@
+use cosmwasm_std::{Addr, Deps, Order};
+use cw_storage_plus::Bound;
+
+use crate::{
+    error::ContractError,
+    models::ClaimRecord,
+    msg::{CampaignClaimsQueryMsg, CampaignClaimsResponse, CampaignOrderKey, CampaignOrderKeyCursor},
+    state::{CAMPAIGNS, CLAIM_RECORDS, NAME_ADDR_2_CLAIM_ID, NAME_AMOUNT_2_CLAIM_ID},
+};
+
+use super::{claims::DEFAULT_LIMIT, ReadonlyContext};
+
+/// Paginate all pending claims in a campaign, by descending amount or by
+/// ascending recipient address.
+pub fn query_claims_by_campaign(
+    ctx: ReadonlyContext,
+    msg: CampaignClaimsQueryMsg,
+) -> Result<CampaignClaimsResponse, ContractError> {
+    let ReadonlyContext { deps, .. } = ctx;
+    let CampaignClaimsQueryMsg { name, order_by, cursor } = msg;
+
+    CAMPAIGNS.load(deps.storage, &name)?;
+
+    let (claims, next_cursor) = match order_by {
+        CampaignOrderKey::Amount => {
+            let start_from = if let Some(CampaignOrderKeyCursor::Amount((n, id))) = cursor {
+                Some((n.u128(), id.u64()))
+            } else {
+                None
+            };
+            campaign_claims_by_amount(deps, &name, start_from)
+        },
+        CampaignOrderKey::Recipient => {
+            let start_from = if let Some(CampaignOrderKeyCursor::Recipient((addr, id))) = cursor {
+                Some((addr, id.u64()))
+            } else {
+                None
+            };
+            campaign_claims_by_recipient(deps, &name, start_from)
+        },
+    }?;
+
+    Ok(CampaignClaimsResponse {
+        claims,
+        cursor: next_cursor,
+    })
+}
+
+fn campaign_claims_by_amount(
+    deps: Deps,
+    name: &String,
+    start_from: Option<(u128, u64)>,
+) -> Result<(Vec<ClaimRecord>, Option<CampaignOrderKeyCursor>), ContractError> {
+    let max_bound = start_from.map(Bound::exclusive);
+
+    let mut records: Vec<ClaimRecord> = Vec::with_capacity(8);
+    let mut next_cursor: Option<CampaignOrderKeyCursor> = None;
+
+    for result in NAME_AMOUNT_2_CLAIM_ID
+        .sub_prefix(name)
+        .keys(deps.storage, None, max_bound, Order::Descending)
+        .take(DEFAULT_LIMIT)
+    {
+        let (amount, id) = result?;
+        records.push(CLAIM_RECORDS.load(deps.storage, id)?);
+        next_cursor = Some(CampaignOrderKeyCursor::Amount((amount.into(), id.into())));
+    }
+
+    if records.len() < DEFAULT_LIMIT {
+        next_cursor = None;
+    };
+
+    Ok((records, next_cursor))
+}
+
+fn campaign_claims_by_recipient(
+    deps: Deps,
+    name: &String,
+    start_from: Option<(Addr, u64)>,
+) -> Result<(Vec<ClaimRecord>, Option<CampaignOrderKeyCursor>), ContractError> {
+    let min_bound = start_from.as_ref().map(|(addr, id)| Bound::exclusive((addr, *id)));
+
+    let mut records: Vec<ClaimRecord> = Vec::with_capacity(8);
+    let mut next_cursor: Option<CampaignOrderKeyCursor> = None;
+
+    for result in NAME_ADDR_2_CLAIM_ID
+        .sub_prefix(name)
+        .keys(deps.storage, min_bound, None, Order::Ascending)
+        .take(DEFAULT_LIMIT)
+    {
+        let (addr, id) = result?;
+        records.push(CLAIM_RECORDS.load(deps.storage, id)?);
+        next_cursor = Some(CampaignOrderKeyCursor::Recipient((addr, id.into())));
+    }
+
+    if records.len() < DEFAULT_LIMIT {
+        next_cursor = None;
+    };
+
+    Ok((records, next_cursor))
+}
@@ -0,0 +1,88 @@
+use cosmwasm_std::Order;
+use cw_storage_plus::Bound;
+
+use crate::{
+    error::ContractError,
+    msg::{CampaignResponse, CampaignsResponse, TokenSourcesResponse, TokenTotalsResponse},
+    state::{CAMPAIGNS, CAMPAIGN_TOKEN_SOURCES, CAMPAIGN_TOKEN_TOTALS},
+};
+
+use super::{claims::DEFAULT_LIMIT, ReadonlyContext};
+
+pub fn query_campaign(
+    ctx: ReadonlyContext,
+    name: String,
+) -> Result<CampaignResponse, ContractError> {
+    let ReadonlyContext { deps, .. } = ctx;
+    Ok(CampaignResponse(CAMPAIGNS.load(deps.storage, &name)?))
+}
+
+pub fn query_campaigns(
+    ctx: ReadonlyContext,
+    cursor: Option<String>,
+) -> Result<CampaignsResponse, ContractError> {
+    let ReadonlyContext { deps, .. } = ctx;
+    let min_bound = cursor.as_ref().map(Bound::exclusive);
+
+    let campaigns = CAMPAIGNS
+        .range(deps.storage, min_bound, None, Order::Ascending)
+        .take(DEFAULT_LIMIT)
+        .map(|r| r.map(|(_, campaign)| campaign))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let next_cursor = if campaigns.len() < DEFAULT_LIMIT {
+        None
+    } else {
+        campaigns.last().map(|c| c.name.to_owned())
+    };
+
+    Ok(CampaignsResponse {
+        campaigns,
+        cursor: next_cursor,
+    })
+}
+
+/// Denoms and token contracts that a campaign accepts deposits of.
+pub fn query_token_sources(
+    ctx: ReadonlyContext,
+    name: String,
+) -> Result<TokenSourcesResponse, ContractError> {
+    let ReadonlyContext { deps, .. } = ctx;
+
+    let sources = CAMPAIGN_TOKEN_SOURCES
+        .prefix(&name)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|r| r.map(|(_, source)| source))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(TokenSourcesResponse { sources })
+}
+
+/// Deposited, claimed, recovered and outstanding totals of each token in a
+/// campaign, paginated by token key.
+pub fn query_token_totals(
+    ctx: ReadonlyContext,
+    name: String,
+    cursor: Option<String>,
+) -> Result<TokenTotalsResponse, ContractError> {
+    let ReadonlyContext { deps, .. } = ctx;
+    let min_bound = cursor.as_ref().map(Bound::exclusive);
+
+    let totals = CAMPAIGN_TOKEN_TOTALS
+        .prefix(&name)
+        .range(deps.storage, min_bound, None, Order::Ascending)
+        .take(DEFAULT_LIMIT)
+        .map(|r| r.map(|(_, totals)| totals))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let next_cursor = if totals.len() < DEFAULT_LIMIT {
+        None
+    } else {
+        totals.last().map(|t| t.token.to_key())
+    };
+
+    Ok(TokenTotalsResponse {
+        totals,
+        cursor: next_cursor,
+    })
+}
@@ -0,0 +1,57 @@
+use cosmwasm_std::{Addr, Uint64};
+
+use crate::{
+    error::ContractError,
+    msg::{ClaimResponse, ClaimsByIdsResponse},
+    state::{CLAIM_RECORDS, ID_LUTAB},
+    token::Token,
+};
+
+use super::{claims::DEFAULT_LIMIT, ReadonlyContext};
+
+pub fn query_claim(
+    ctx: ReadonlyContext,
+    id: Uint64,
+) -> Result<ClaimResponse, ContractError> {
+    let ReadonlyContext { deps, .. } = ctx;
+    Ok(ClaimResponse(CLAIM_RECORDS.may_load(deps.storage, id.u64())?))
+}
+
+pub fn query_claim_by_key(
+    ctx: ReadonlyContext,
+    name: String,
+    token: Token,
+    recipient: Addr,
+) -> Result<ClaimResponse, ContractError> {
+    let ReadonlyContext { deps, .. } = ctx;
+    Ok(ClaimResponse(
+        if let Some(id) = ID_LUTAB.may_load(deps.storage, (&name, &token.to_key(), &recipient))? {
+            CLAIM_RECORDS.may_load(deps.storage, id.u64())?
+        } else {
+            None
+        },
+    ))
+}
+
+/// Get existing claim records for a batch of IDs, skipping any not found.
+pub fn query_claims_by_ids(
+    ctx: ReadonlyContext,
+    ids: Vec<Uint64>,
+) -> Result<ClaimsByIdsResponse, ContractError> {
+    let ReadonlyContext { deps, .. } = ctx;
+
+    if ids.len() > DEFAULT_LIMIT {
+        return Err(ContractError::ValidationError {
+            reason: format!("cannot query more than {} claims at a time", DEFAULT_LIMIT),
+        });
+    }
+
+    let mut claims = Vec::with_capacity(ids.len());
+    for id in ids {
+        if let Some(claim) = CLAIM_RECORDS.may_load(deps.storage, id.u64())? {
+            claims.push(claim);
+        }
+    }
+
+    Ok(ClaimsByIdsResponse { claims })
+}
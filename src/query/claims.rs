@@ -1,13 +1,12 @@
-use std::marker::PhantomData;
-
-use cosmwasm_std::{Addr, Order, Uint64};
+use cosmwasm_std::{Addr, Deps, Order, Uint64};
 use cw_storage_plus::Bound;
 
 use crate::{
     error::ContractError,
     models::ClaimRecord,
     msg::{ClaimsQueryMsg, ClaimsResponse, OrderKey, OrderKeyCursor},
-    state::{AMOUNT_2_CLAIM_ID, CLAIM_RECORDS, TIME_2_CLAIM_ID, TOKEN_2_CLAIM_ID},
+    state::{ADDR_NAME_2_CLAIM_ID, AMOUNT_2_CLAIM_ID, CLAIM_RECORDS, ID_LUTAB, TIME_2_CLAIM_ID, TOKEN_2_CLAIM_ID},
+    token::Token,
 };
 
 use super::ReadonlyContext;
@@ -18,37 +17,49 @@ pub fn query_claims(
     ctx: ReadonlyContext,
     msg: ClaimsQueryMsg,
 ) -> Result<ClaimsResponse, ContractError> {
+    let ReadonlyContext { deps, .. } = ctx;
     let ClaimsQueryMsg {
         address,
         order_by,
         cursor,
+        token,
+        name,
     } = msg;
 
-    let (claims, next_cursor) = match order_by {
-        OrderKey::UpdatedAt => {
-            let start_from = if let Some(OrderKeyCursor::UpdatedAt((n, id))) = cursor {
-                Some((n.u64(), id.into()))
-            } else {
-                None
-            };
-            claims_by_time(ctx, address, start_from)
-        },
-        OrderKey::Amount => {
-            let start_from = if let Some(OrderKeyCursor::Amount((n, id))) = cursor {
-                Some((n.into(), id.into()))
-            } else {
-                None
-            };
-            claims_by_amount(ctx, address, start_from)
-        },
-        OrderKey::Token => {
-            let start_from = if let Some(OrderKeyCursor::Token(x)) = cursor {
-                Some(x)
-            } else {
-                None
-            };
-            claims_by_token(ctx, address, start_from)
-        },
+    let (claims, next_cursor) = if token.is_some() || name.is_some() {
+        let start_from = if let Some(OrderKeyCursor::Id(id)) = cursor {
+            Some(id.u64())
+        } else {
+            None
+        };
+        claims_by_filter(deps, &address, token, name, start_from)
+    } else {
+        match order_by {
+            OrderKey::UpdatedAt => {
+                let start_from = if let Some(OrderKeyCursor::UpdatedAt((n, id))) = cursor {
+                    Some((n.u64(), id.into()))
+                } else {
+                    None
+                };
+                claims_by_time(deps, &address, start_from)
+            },
+            OrderKey::Amount => {
+                let start_from = if let Some(OrderKeyCursor::Amount((n, id))) = cursor {
+                    Some((n.into(), id.into()))
+                } else {
+                    None
+                };
+                claims_by_amount(deps, &address, start_from)
+            },
+            OrderKey::Token => {
+                let start_from = if let Some(OrderKeyCursor::Token(x)) = cursor {
+                    Some(x)
+                } else {
+                    None
+                };
+                claims_by_token(deps, &address, start_from)
+            },
+        }
     }?;
 
     Ok(ClaimsResponse {
@@ -58,24 +69,23 @@ pub fn query_claims(
 }
 
 fn claims_by_time(
-    ctx: ReadonlyContext,
-    address: Addr,
+    deps: Deps,
+    address: &Addr,
     start_from: Option<(u64, u64)>,
 ) -> Result<(Vec<ClaimRecord>, Option<OrderKeyCursor>), ContractError> {
-    let ReadonlyContext { deps, .. } = ctx;
-    let max_bound = if let Some((n, id)) = start_from {
-        Some(Bound::Exclusive(((&address, n, id), PhantomData)))
-    } else {
-        Some(Bound::Inclusive(((&address, 0, 0), PhantomData)))
-    };
+    let max_bound = start_from.map(Bound::exclusive);
 
     let mut records: Vec<ClaimRecord> = Vec::with_capacity(8);
     let mut next_cursor: Option<OrderKeyCursor> = None;
 
-    for result in TIME_2_CLAIM_ID.range(deps.storage, None, max_bound, Order::Descending) {
-        let ((_, t, id), _) = result?;
+    for result in TIME_2_CLAIM_ID
+        .sub_prefix(address)
+        .keys(deps.storage, None, max_bound, Order::Descending)
+        .take(DEFAULT_LIMIT)
+    {
+        let (t, id) = result?;
         records.push(CLAIM_RECORDS.load(deps.storage, id)?);
-        next_cursor = Some(OrderKeyCursor::Amount((t.into(), id.into())));
+        next_cursor = Some(OrderKeyCursor::UpdatedAt((t.into(), id.into())));
     }
 
     if records.len() < DEFAULT_LIMIT {
@@ -86,22 +96,21 @@ fn claims_by_time(
 }
 
 fn claims_by_amount(
-    ctx: ReadonlyContext,
-    address: Addr,
+    deps: Deps,
+    address: &Addr,
     start_from: Option<(u128, u64)>,
 ) -> Result<(Vec<ClaimRecord>, Option<OrderKeyCursor>), ContractError> {
-    let ReadonlyContext { deps, .. } = ctx;
-    let max_bound = if let Some((n, id)) = start_from {
-        Some(Bound::Exclusive(((&address, n, id), PhantomData)))
-    } else {
-        Some(Bound::Inclusive(((&address, 0, 0), PhantomData)))
-    };
+    let max_bound = start_from.map(Bound::exclusive);
 
     let mut records: Vec<ClaimRecord> = Vec::with_capacity(8);
     let mut next_cursor: Option<OrderKeyCursor> = None;
 
-    for result in AMOUNT_2_CLAIM_ID.range(deps.storage, None, max_bound, Order::Descending) {
-        let ((_, amount, id), _) = result?;
+    for result in AMOUNT_2_CLAIM_ID
+        .sub_prefix(address)
+        .keys(deps.storage, None, max_bound, Order::Descending)
+        .take(DEFAULT_LIMIT)
+    {
+        let (amount, id) = result?;
         records.push(CLAIM_RECORDS.load(deps.storage, id)?);
         next_cursor = Some(OrderKeyCursor::Amount((amount.into(), id.into())));
     }
@@ -114,24 +123,23 @@ fn claims_by_amount(
 }
 
 fn claims_by_token(
-    ctx: ReadonlyContext,
-    address: Addr,
+    deps: Deps,
+    address: &Addr,
     start_from: Option<(String, Uint64)>,
 ) -> Result<(Vec<ClaimRecord>, Option<OrderKeyCursor>), ContractError> {
-    let ReadonlyContext { deps, .. } = ctx;
-    let mut boxed_str: Box<String> = Box::new("".to_owned());
-    let max_bound = if let Some((token_key, id)) = start_from {
-        *boxed_str = token_key;
-        Some(Bound::Exclusive(((&address, &*boxed_str, id.u64()), PhantomData)))
-    } else {
-        Some(Bound::Inclusive(((&address, &*boxed_str, 0u64), PhantomData)))
-    };
+    let max_bound = start_from
+        .as_ref()
+        .map(|(token_key, id)| Bound::exclusive((token_key, id.u64())));
 
     let mut records: Vec<ClaimRecord> = Vec::with_capacity(8);
     let mut next_cursor: Option<OrderKeyCursor> = None;
 
-    for result in TOKEN_2_CLAIM_ID.range(deps.storage, None, max_bound, Order::Descending) {
-        let ((_, token_key, id), _) = result?;
+    for result in TOKEN_2_CLAIM_ID
+        .sub_prefix(address)
+        .keys(deps.storage, None, max_bound, Order::Descending)
+        .take(DEFAULT_LIMIT)
+    {
+        let (token_key, id) = result?;
         records.push(CLAIM_RECORDS.load(deps.storage, id)?);
         next_cursor = Some(OrderKeyCursor::Token((token_key, id.into())));
     }
@@ -142,3 +150,55 @@ fn claims_by_token(
 
     Ok((records, next_cursor))
 }
+
+/// Paginate an address's claims for a given token and/or campaign by
+/// descending ID, ranging over the relevant index prefix.
+fn claims_by_filter(
+    deps: Deps,
+    address: &Addr,
+    token: Option<Token>,
+    name: Option<String>,
+    start_from: Option<u64>,
+) -> Result<(Vec<ClaimRecord>, Option<OrderKeyCursor>), ContractError> {
+    let max_bound = start_from.map(Bound::exclusive);
+
+    let ids: Vec<u64> = match (token, name) {
+        // There's at most one claim per (name, token, address)
+        (Some(token), Some(name)) => {
+            if let Some(id) = ID_LUTAB.may_load(deps.storage, (&name, &token.to_key(), address))? {
+                let id = id.u64();
+                if start_from.map(|n| id < n).unwrap_or(true) && CLAIM_RECORDS.has(deps.storage, id) {
+                    vec![id]
+                } else {
+                    vec![]
+                }
+            } else {
+                vec![]
+            }
+        },
+        (Some(token), None) => TOKEN_2_CLAIM_ID
+            .prefix((address, &token.to_key()))
+            .keys(deps.storage, None, max_bound, Order::Descending)
+            .take(DEFAULT_LIMIT)
+            .collect::<Result<Vec<_>, _>>()?,
+        (None, Some(name)) => ADDR_NAME_2_CLAIM_ID
+            .prefix((address, &name))
+            .keys(deps.storage, None, max_bound, Order::Descending)
+            .take(DEFAULT_LIMIT)
+            .collect::<Result<Vec<_>, _>>()?,
+        (None, None) => vec![],
+    };
+
+    let mut records: Vec<ClaimRecord> = Vec::with_capacity(ids.len());
+    for id in ids.iter() {
+        records.push(CLAIM_RECORDS.load(deps.storage, *id)?);
+    }
+
+    let next_cursor = if records.len() < DEFAULT_LIMIT {
+        None
+    } else {
+        ids.last().map(|id| OrderKeyCursor::Id((*id).into()))
+    };
+
+    Ok((records, next_cursor))
+}
@@ -1,8 +1,8 @@
-use crate::{error::ContractError, models::Config, msg::ConfigResponse};
+use crate::{error::ContractError, msg::ConfigResponse, state::CONFIG};
 
 use super::ReadonlyContext;
 
 pub fn query_config(ctx: ReadonlyContext) -> Result<ConfigResponse, ContractError> {
-    let ReadonlyContext { .. } = ctx;
-    Ok(ConfigResponse(Config {}))
+    let ReadonlyContext { deps, .. } = ctx;
+    Ok(ConfigResponse(CONFIG.load(deps.storage)?))
 }
@@ -0,0 +1,65 @@
+use cosmwasm_std::{Addr, Order, Uint64};
+use cw_storage_plus::Bound;
+
+use crate::{
+    error::ContractError,
+    msg::{ClaimHistoryResponse, ClaimHistoryScope, ClaimedTotalsResponse},
+    state::{ADDR_2_HISTORY_ID, CLAIMED_TOTALS, CLAIM_HISTORY, NAME_2_HISTORY_ID},
+};
+
+use super::{claims::DEFAULT_LIMIT, ReadonlyContext};
+
+/// Paginate claim history for an address or campaign, newest first.
+pub fn query_claim_history(
+    ctx: ReadonlyContext,
+    scope: ClaimHistoryScope,
+    cursor: Option<Uint64>,
+) -> Result<ClaimHistoryResponse, ContractError> {
+    let ReadonlyContext { deps, .. } = ctx;
+    let max_bound = cursor.map(|id| Bound::exclusive(id.u64()));
+
+    let history_ids = match &scope {
+        ClaimHistoryScope::Address(address) => ADDR_2_HISTORY_ID
+            .prefix(address)
+            .keys(deps.storage, None, max_bound, Order::Descending)
+            .take(DEFAULT_LIMIT)
+            .collect::<Result<Vec<_>, _>>()?,
+        ClaimHistoryScope::Campaign(name) => NAME_2_HISTORY_ID
+            .prefix(name)
+            .keys(deps.storage, None, max_bound, Order::Descending)
+            .take(DEFAULT_LIMIT)
+            .collect::<Result<Vec<_>, _>>()?,
+    };
+
+    let mut entries = Vec::with_capacity(history_ids.len());
+    for id in history_ids.iter() {
+        entries.push(CLAIM_HISTORY.load(deps.storage, *id)?);
+    }
+
+    let next_cursor = if history_ids.len() < DEFAULT_LIMIT {
+        None
+    } else {
+        history_ids.last().map(|id| (*id).into())
+    };
+
+    Ok(ClaimHistoryResponse {
+        entries,
+        cursor: next_cursor,
+    })
+}
+
+/// Lifetime totals claimed by an address, per token.
+pub fn query_claimed_totals(
+    ctx: ReadonlyContext,
+    address: Addr,
+) -> Result<ClaimedTotalsResponse, ContractError> {
+    let ReadonlyContext { deps, .. } = ctx;
+
+    let totals = CLAIMED_TOTALS
+        .prefix(&address)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|r| r.map(|(_, total)| total))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(ClaimedTotalsResponse { totals })
+}
@@ -0,0 +1,30 @@
+use cosmwasm_std::Order;
+
+use crate::{
+    error::ContractError,
+    msg::HooksResponse,
+    state::{CAMPAIGN_HOOKS, GLOBAL_HOOKS},
+};
+
+use super::ReadonlyContext;
+
+/// Hooks registered for a campaign, or global hooks if no name is given.
+pub fn query_hooks(
+    ctx: ReadonlyContext,
+    name: Option<String>,
+) -> Result<HooksResponse, ContractError> {
+    let ReadonlyContext { deps, .. } = ctx;
+
+    let hooks = if let Some(name) = &name {
+        CAMPAIGN_HOOKS
+            .prefix(name)
+            .keys(deps.storage, None, None, Order::Ascending)
+            .collect::<Result<Vec<_>, _>>()?
+    } else {
+        GLOBAL_HOOKS
+            .keys(deps.storage, None, None, Order::Ascending)
+            .collect::<Result<Vec<_>, _>>()?
+    };
+
+    Ok(HooksResponse { hooks })
+}
@@ -1,5 +1,12 @@
+pub mod campaign_claims;
+pub mod campaigns;
+pub mod claim;
 pub mod claims;
 pub mod config;
+pub mod history;
+pub mod hooks;
+pub mod simulate;
+pub mod summary;
 
 use cosmwasm_std::{Deps, Env};
 
@@ -0,0 +1,38 @@
+use cosmwasm_std::{Addr, Uint128, Uint64};
+
+use crate::{
+    error::ContractError,
+    execute::claim::plan_claim,
+    msg::{ClaimFilter, SimulateClaimResponse},
+};
+
+use super::ReadonlyContext;
+
+/// Simulate what a claim would pay out, without modifying state.
+pub fn query_simulate_claim(
+    ctx: ReadonlyContext,
+    address: Addr,
+    ids: Option<Vec<Uint64>>,
+    recipient: Option<Addr>,
+    filter: Option<ClaimFilter>,
+    cursor: Option<Uint64>,
+    amount: Option<Uint128>,
+) -> Result<SimulateClaimResponse, ContractError> {
+    let ReadonlyContext { deps, env } = ctx;
+
+    let recipient = if let Some(recipient) = recipient {
+        deps.api.addr_validate(recipient.as_str())?
+    } else {
+        address.to_owned()
+    };
+
+    let plan = plan_claim(deps.storage, &env.block, &address, ids, filter, cursor, amount)?;
+
+    Ok(SimulateClaimResponse {
+        recipient,
+        payouts: plan.payouts(),
+        ids: plan.ids.into_iter().map(|id| id.into()).collect(),
+        has_more: plan.has_more,
+        cursor: plan.cursor,
+    })
+}
@@ -0,0 +1,21 @@
+use cosmwasm_std::{Addr, Order};
+
+use crate::{error::ContractError, msg::ClaimableSummaryResponse, state::PENDING_TOTALS};
+
+use super::ReadonlyContext;
+
+/// Total amount pending for an address and number of claim records, per token.
+pub fn query_claimable_summary(
+    ctx: ReadonlyContext,
+    address: Addr,
+) -> Result<ClaimableSummaryResponse, ContractError> {
+    let ReadonlyContext { deps, .. } = ctx;
+
+    let totals = PENDING_TOTALS
+        .prefix(&address)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|r| r.map(|(_, total)| total))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(ClaimableSummaryResponse { totals })
+}
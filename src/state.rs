@@ -1,8 +1,27 @@
-use cosmwasm_std::{Addr, Response, Uint64};
+use std::ops::Range;
+
+use cosmwasm_std::{Addr, Response, Storage, Uint128, Uint64};
 use cw_storage_plus::{Item, Map};
 
-use crate::{error::ContractError, execute::Context, models::ClaimRecord, msg::InstantiateMsg};
+use crate::{
+    error::ContractError,
+    execute::Context,
+    math::add_u64,
+    models::{
+        Campaign, CampaignTokenTotals, ClaimHistoryEntry, ClaimRecord, Config, IbcTransfer, Payout, PendingTotal,
+    },
+    msg::InstantiateMsg,
+    token::{TokenAmount, TokenSource},
+};
+
+/// Max keeper fee that may be configured (5%), in parts per million.
+pub const MAX_KEEPER_FEE_PCT: u128 = 50_000;
 
+pub const OWNER: Item<Addr> = Item::new("owner");
+pub const CONFIG: Item<Config> = Item::new("config");
+pub const CAMPAIGNS: Map<&String, Campaign> = Map::new("campaigns");
+pub const CAMPAIGN_TOKEN_SOURCES: Map<(&String, &String), TokenSource> = Map::new("campaign_token_sources");
+pub const CAMPAIGN_TOKEN_TOTALS: Map<(&String, &String), CampaignTokenTotals> = Map::new("campaign_token_totals");
 pub const ID_COUNTER: Item<Uint64> = Item::new("id_counter");
 pub const ID_LUTAB: Map<(&String, &String, &Addr), Uint64> = Map::new("id_lutab");
 pub const CLAIM_RECORDS: Map<u64, ClaimRecord> = Map::new("claim_records");
@@ -10,11 +29,61 @@ pub const ADDR_2_CLAIM_ID: Map<(&Addr, u64), ()> = Map::new("addr_2_claim_id");
 pub const TIME_2_CLAIM_ID: Map<(&Addr, u64, u64), ()> = Map::new("time_2_claim_id");
 pub const AMOUNT_2_CLAIM_ID: Map<(&Addr, u128, u64), ()> = Map::new("amount_2_claim_id");
 pub const TOKEN_2_CLAIM_ID: Map<(&Addr, &String, u64), ()> = Map::new("token_2_claim_id");
+pub const ADDR_NAME_2_CLAIM_ID: Map<(&Addr, &String, u64), ()> = Map::new("addr_name_2_claim_id");
+pub const NAME_AMOUNT_2_CLAIM_ID: Map<(&String, u128, u64), ()> = Map::new("name_amount_2_claim_id");
+pub const NAME_ADDR_2_CLAIM_ID: Map<(&String, &Addr, u64), ()> = Map::new("name_addr_2_claim_id");
+pub const PENDING_TOTALS: Map<(&Addr, &String), PendingTotal> = Map::new("pending_totals");
+pub const HISTORY_COUNTER: Item<Uint64> = Item::new("history_counter");
+pub const CLAIM_HISTORY: Map<u64, ClaimHistoryEntry> = Map::new("claim_history");
+pub const ADDR_2_HISTORY_ID: Map<(&Addr, u64), ()> = Map::new("addr_2_history_id");
+pub const NAME_2_HISTORY_ID: Map<(&String, u64), ()> = Map::new("name_2_history_id");
+pub const CLAIMED_TOTALS: Map<(&Addr, &String), TokenAmount> = Map::new("claimed_totals");
+pub const GLOBAL_HOOKS: Map<&Addr, ()> = Map::new("global_hooks");
+pub const CAMPAIGN_HOOKS: Map<(&String, &Addr), ()> = Map::new("campaign_hooks");
+pub const IBC_TRANSFER_COUNTER: Item<Uint64> = Item::new("ibc_transfer_counter");
+pub const PENDING_IBC_TRANSFERS: Map<u64, IbcTransfer> = Map::new("pending_ibc_transfers");
+pub const IBC_TRANSFERS: Map<(&str, u64), IbcTransfer> = Map::new("ibc_transfers");
+pub const PAYOUT_COUNTER: Item<Uint64> = Item::new("payout_counter");
+pub const PENDING_PAYOUTS: Map<u64, Payout> = Map::new("pending_payouts");
+pub const DISTRIBUTION_CURSORS: Map<&String, (String, Addr)> = Map::new("distribution_cursors");
+pub const RECOVERY_CURSORS: Map<&String, (String, Addr)> = Map::new("recovery_cursors");
 
 /// Top-level initialization of contract state
 pub fn init(
-    _ctx: Context,
-    _msg: InstantiateMsg,
+    ctx: Context,
+    msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
+    let Context { deps, info, .. } = ctx;
+
+    validate_config(&msg.config)?;
+
+    OWNER.save(deps.storage, &info.sender)?;
+    CONFIG.save(deps.storage, &msg.config)?;
+    ID_COUNTER.save(deps.storage, &Uint64::zero())?;
+    HISTORY_COUNTER.save(deps.storage, &Uint64::zero())?;
+
     Ok(Response::new().add_attribute("action", "instantiate"))
 }
+
+pub fn validate_config(config: &Config) -> Result<(), ContractError> {
+    if let Some(pct) = config.keeper_fee_pct {
+        if pct > Uint128::from(MAX_KEEPER_FEE_PCT) {
+            return Err(ContractError::ValidationError {
+                reason: format!("keeper fee cannot exceed {} parts per million", MAX_KEEPER_FEE_PCT),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Take the next reply ID from a reserved range, wrapping around to its start
+/// once the range is exhausted.
+pub fn next_reply_id(
+    store: &mut dyn Storage,
+    counter: &Item<Uint64>,
+    ids: &Range<u64>,
+) -> Result<u64, ContractError> {
+    let n = counter.may_load(store)?.unwrap_or_default();
+    counter.save(store, &add_u64(n, 1u64)?)?;
+    Ok(ids.start + n.u64() % (ids.end - ids.start))
+}
@@ -1,22 +1,69 @@
+use std::collections::BTreeMap;
+
 use cosmwasm_schema::cw_serde;
 use cosmwasm_std::{
-    to_json_binary, Addr, BankMsg, Coin, CosmosMsg, Empty, QuerierWrapper, StdError, StdResult, SubMsg, Uint128,
-    Uint64, WasmMsg,
+    to_json_binary, Addr, BankMsg, Binary, Coin, CosmosMsg, Empty, QuerierWrapper, StdError, StdResult, SubMsg,
+    Uint128, Uint64, WasmMsg,
 };
 use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg};
 
+use crate::{
+    cw1155::{self, Cw1155ExecuteMsg, Cw1155QueryMsg},
+    cw721::{Cw721ExecuteMsg, Cw721QueryMsg, OwnerOfResponse},
+    proto::ProtoBuf,
+};
+
 #[cw_serde]
 #[derive(Eq, Hash)]
 pub enum Token {
     Denom(String),
     Address(Addr),
+    /// Single NFT in a cw721 collection. Claim amounts are always 1.
+    Nft {
+        collection: Addr,
+        token_id: String,
+    },
+    /// Fungible token ID in a cw1155 contract.
+    MultiToken {
+        contract: Addr,
+        token_id: String,
+    },
+}
+
+/// Where a token comes from: a native denom, or the cw20, cw721, or cw1155
+/// contract that issues it.
+#[cw_serde]
+#[derive(Eq, Hash)]
+pub enum TokenSource {
+    Denom(String),
+    Contract(Addr),
+}
+
+impl TokenSource {
+    pub fn to_key(&self) -> String {
+        match self {
+            Self::Denom(denom) => format!("d:{}", denom),
+            Self::Contract(address) => format!("a:{}", address),
+        }
+    }
 }
 
 impl Token {
     pub fn to_key(&self) -> String {
         match self {
-            Self::Address(address) => format!("a:{}", address.to_string()),
+            Self::Address(address) => format!("a:{}", address),
             Self::Denom(denom) => format!("d:{}", denom),
+            Self::Nft { collection, token_id } => format!("n:{}:{}", collection, token_id),
+            Self::MultiToken { contract, token_id } => format!("m:{}:{}", contract, token_id),
+        }
+    }
+
+    pub fn source(&self) -> TokenSource {
+        match self {
+            Self::Denom(denom) => TokenSource::Denom(denom.to_owned()),
+            Self::Address(address) => TokenSource::Contract(address.to_owned()),
+            Self::Nft { collection, .. } => TokenSource::Contract(collection.to_owned()),
+            Self::MultiToken { contract, .. } => TokenSource::Contract(contract.to_owned()),
         }
     }
 
@@ -53,12 +100,38 @@ impl Token {
                 )?;
                 balance
             },
+            Self::Nft { collection, token_id } => {
+                let OwnerOfResponse { owner } = querier.query_wasm_smart(
+                    collection.clone(),
+                    &Cw721QueryMsg::OwnerOf {
+                        token_id: token_id.clone(),
+                        include_expired: None,
+                    },
+                )?;
+                if owner == *address {
+                    Uint128::one()
+                } else {
+                    Uint128::zero()
+                }
+            },
+            Self::MultiToken { contract, token_id } => {
+                let cw1155::BalanceResponse { balance } = querier.query_wasm_smart(
+                    contract.clone(),
+                    &Cw1155QueryMsg::Balance {
+                        owner: address.to_string(),
+                        token_id: token_id.clone(),
+                    },
+                )?;
+                balance
+            },
         })
     }
 
-    /// Send token amount without triggering side-effects
+    /// Send token amount held by the sender, which must be this contract,
+    /// without triggering side-effects
     pub fn transfer(
         &self,
+        sender: &Addr,
         recipient: &Addr,
         amount: Uint128,
     ) -> StdResult<SubMsg> {
@@ -75,35 +148,114 @@ impl Token {
                 })?,
                 funds: vec![],
             }),
+            Self::Nft { collection, token_id } => {
+                ensure_single_nft(amount)?;
+                SubMsg::new(WasmMsg::Execute {
+                    contract_addr: collection.clone().into(),
+                    msg: to_json_binary(&Cw721ExecuteMsg::TransferNft {
+                        recipient: recipient.clone().into(),
+                        token_id: token_id.clone(),
+                    })?,
+                    funds: vec![],
+                })
+            },
+            Self::MultiToken { contract, token_id } => {
+                batch_send_from(contract, sender, recipient, vec![(token_id.clone(), amount)])?
+            },
         })
     }
+
+    /// Burn token amount held by the sender, which must be this contract.
+    /// Native denoms are burned through the chain's tokenfactory module.
     pub fn burn(
         &self,
+        sender: &Addr,
         amount: Uint128,
+        tokenfactory: Option<&TokenFactory>,
     ) -> StdResult<SubMsg> {
         Ok(match self {
-            Self::Denom(_denom) => {
-                return Err(StdError::GenericErr {
-                    msg: "tokenfactory burn not implemented".to_owned(),
-                });
+            Self::Denom(denom) => {
+                let tokenfactory = tokenfactory.ok_or_else(|| StdError::GenericErr {
+                    msg: "tokenfactory module not configured".to_owned(),
+                })?;
+                SubMsg::new(tokenfactory.msg("MsgBurn", sender, denom, amount))
             },
             Self::Address(cw20_addr) => SubMsg::new(WasmMsg::Execute {
                 contract_addr: cw20_addr.clone().into(),
                 msg: to_json_binary(&Cw20ExecuteMsg::Burn { amount })?,
                 funds: vec![],
             }),
+            Self::Nft { collection, token_id } => {
+                ensure_single_nft(amount)?;
+                SubMsg::new(WasmMsg::Execute {
+                    contract_addr: collection.clone().into(),
+                    msg: to_json_binary(&Cw721ExecuteMsg::Burn {
+                        token_id: token_id.clone(),
+                    })?,
+                    funds: vec![],
+                })
+            },
+            Self::MultiToken { contract, token_id } => SubMsg::new(WasmMsg::Execute {
+                contract_addr: contract.clone().into(),
+                msg: to_json_binary(&Cw1155ExecuteMsg::BatchBurn {
+                    from: sender.clone().into(),
+                    batch: vec![(token_id.clone(), amount)],
+                })?,
+                funds: vec![],
+            }),
+        })
+    }
+
+    /// Mint token amount to the sender, which must be this contract and an
+    /// admin of the denom or minter of the cw20.
+    pub fn mint(
+        &self,
+        sender: &Addr,
+        amount: Uint128,
+        tokenfactory: Option<&TokenFactory>,
+    ) -> StdResult<SubMsg> {
+        Ok(match self {
+            Self::Denom(denom) => {
+                let tokenfactory = tokenfactory.ok_or_else(|| StdError::GenericErr {
+                    msg: "tokenfactory module not configured".to_owned(),
+                })?;
+                SubMsg::new(tokenfactory.msg("MsgMint", sender, denom, amount))
+            },
+            Self::Address(cw20_addr) => SubMsg::new(WasmMsg::Execute {
+                contract_addr: cw20_addr.clone().into(),
+                msg: to_json_binary(&Cw20ExecuteMsg::Mint {
+                    recipient: sender.clone().into(),
+                    amount,
+                })?,
+                funds: vec![],
+            }),
+            Self::Nft { .. } => {
+                return Err(StdError::GenericErr {
+                    msg: "NFTs cannot be minted on claim".to_owned(),
+                });
+            },
+            Self::MultiToken { contract, token_id } => SubMsg::new(WasmMsg::Execute {
+                contract_addr: contract.clone().into(),
+                msg: to_json_binary(&Cw1155ExecuteMsg::Mint {
+                    to: sender.clone().into(),
+                    token_id: token_id.clone(),
+                    value: amount,
+                    msg: None,
+                })?,
+                funds: vec![],
+            }),
         })
     }
 
     pub fn find_in_funds(
         &self,
-        funds_to_search: &Vec<Coin>,
+        funds_to_search: &[Coin],
         exact_amount: Option<Uint128>,
     ) -> Option<Coin> {
         if let Self::Denom(denom) = self {
             funds_to_search
                 .iter()
-                .find(|c| c.denom == *denom && (exact_amount.and_then(|n| Some(n == c.amount)).unwrap_or(true)))
+                .find(|c| c.denom == *denom && exact_amount.map(|n| n == c.amount).unwrap_or(true))
                 .cloned()
         } else {
             None
@@ -111,6 +263,104 @@ impl Token {
     }
 }
 
+/// Send multiple token amounts held by the sender, which must be this contract,
+/// to one recipient, batching cw1155 tokens into a single `BatchSendFrom` per
+/// contract.
+pub fn transfer_all(
+    sender: &Addr,
+    recipient: &Addr,
+    amounts: &[TokenAmount],
+) -> StdResult<Vec<SubMsg>> {
+    let mut submsgs: Vec<SubMsg> = Vec::with_capacity(amounts.len());
+    let mut batches: BTreeMap<Addr, Vec<(String, Uint128)>> = BTreeMap::new();
+
+    for TokenAmount { token, amount } in amounts.iter() {
+        // Zero-value transfers fail for most tokens, so there's nothing to send
+        if amount.is_zero() {
+            continue;
+        }
+        if let Token::MultiToken { contract, token_id } = token {
+            batches
+                .entry(contract.clone())
+                .or_default()
+                .push((token_id.clone(), *amount));
+        } else {
+            submsgs.push(token.transfer(sender, recipient, *amount)?);
+        }
+    }
+
+    for (contract, batch) in batches.into_iter() {
+        submsgs.push(batch_send_from(&contract, sender, recipient, batch)?);
+    }
+
+    Ok(submsgs)
+}
+
+fn batch_send_from(
+    contract: &Addr,
+    sender: &Addr,
+    recipient: &Addr,
+    batch: Vec<(String, Uint128)>,
+) -> StdResult<SubMsg> {
+    Ok(SubMsg::new(WasmMsg::Execute {
+        contract_addr: contract.clone().into(),
+        msg: to_json_binary(&Cw1155ExecuteMsg::BatchSendFrom {
+            from: sender.clone().into(),
+            to: recipient.clone().into(),
+            batch,
+            msg: None,
+        })?,
+        funds: vec![],
+    }))
+}
+
+fn ensure_single_nft(amount: Uint128) -> StdResult<()> {
+    if amount != Uint128::one() {
+        return Err(StdError::GenericErr {
+            msg: "NFT amount must be exactly 1".to_owned(),
+        });
+    }
+    Ok(())
+}
+
+/// Flavor of tokenfactory module on the host chain, which determines the
+/// protobuf package of its messages.
+#[cw_serde]
+pub enum TokenFactory {
+    /// `osmosis.tokenfactory.v1beta1`
+    Osmosis,
+    /// `osmosis.tokenfactory.v1beta1`, as forked by Juno and others
+    Juno,
+    /// `injective.tokenfactory.v1beta1`
+    Injective,
+}
+
+impl TokenFactory {
+    fn package(&self) -> &'static str {
+        match self {
+            Self::Osmosis | Self::Juno => "osmosis.tokenfactory.v1beta1",
+            Self::Injective => "injective.tokenfactory.v1beta1",
+        }
+    }
+
+    /// Build a `MsgMint` or `MsgBurn`, both of which share the same sender and
+    /// amount fields, acting on the sender's own balance.
+    fn msg(
+        &self,
+        name: &str,
+        sender: &Addr,
+        denom: &str,
+        amount: Uint128,
+    ) -> CosmosMsg {
+        let coin = ProtoBuf::new().string(1, denom).string(2, &amount.to_string());
+        let value = ProtoBuf::new().string(1, sender.as_str()).message(2, coin);
+        CosmosMsg::Stargate {
+            type_url: format!("/{}.{}", self.package(), name),
+            value: Binary::from(value.into_vec()),
+        }
+    }
+}
+
 #[cw_serde]
 pub struct TokenAmount {
     pub token: Token,
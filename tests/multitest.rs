@@ -0,0 +1,2149 @@
+use cosmrs::crypto::secp256k1::SigningKey;
+use cosmwasm_std::{
+    coins, testing::MockStorage, Addr, Api, Binary, BlockInfo, Coin, CustomQuery, Empty, Event, Storage, Uint128,
+    Uint64,
+};
+use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg, TokenInfoResponse};
+use cw_claims::{
+    contract,
+    cw1155::{self, Cw1155ExecuteMsg, Cw1155QueryMsg},
+    cw721::Cw721ReceiveMsg,
+    models::{
+        Campaign, CampaignStatus, ClaimHistoryEntry, Config, ExpiryAction, MintSettings, PendingTotal, RemoteSender,
+    },
+    msg::{
+        CampaignClaimsQueryMsg, CampaignClaimsResponse, CampaignOrderKey, CampaignResponse, ClaimHistoryResponse,
+        ClaimHistoryScope, ClaimMsg, ClaimPaidMsg, ClaimResponse, ClaimableSummaryResponse, ClaimedTotalsResponse,
+        CreateCampaignMsg, DustRecipient, ExecuteMsg, ForeignRecipient, HookExecuteMsg, IbcDestination,
+        IbcLifecycleComplete, InstantiateMsg, KeyProof, QueryMsg, ReceiveMsg, SudoMsg, UpsertMsg, UpsertSharesMsg,
+    },
+    token::{Token, TokenAmount, TokenSource},
+};
+use cw_multi_test::{
+    addons::{MockAddressGenerator, MockApiBech32},
+    error::AnyResult,
+    App, AppBuilder, AppResponse, BankKeeper, ContractWrapper, CosmosRouter, DistributionKeeper, Executor,
+    FailingModule, GovFailingModule, IbcFailingModule, StakeKeeper, Stargate, WasmKeeper,
+};
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use std::fmt::Debug;
+
+const PREFIX: &str = "cosmwasm";
+const DENOM: &str = "ucoin";
+const CAMPAIGN: &str = "airdrop";
+const CHANNEL: &str = "channel-0";
+const PACKET_SEQUENCE: u64 = 7;
+/// 5% in parts per million.
+const KEEPER_FEE_PCT: u128 = 50_000;
+
+/// Accepts ICS-20 transfers, reporting the packet sequence like the chain.
+struct IbcTransferStargate;
+
+impl Stargate for IbcTransferStargate {
+    fn execute<ExecC, QueryC>(
+        &self,
+        _api: &dyn Api,
+        _storage: &mut dyn Storage,
+        _router: &dyn CosmosRouter<ExecC = ExecC, QueryC = QueryC>,
+        _block: &BlockInfo,
+        _sender: Addr,
+        type_url: String,
+        _value: Binary,
+    ) -> AnyResult<AppResponse>
+    where
+        ExecC: Debug + Clone + PartialEq + JsonSchema + DeserializeOwned + 'static,
+        QueryC: CustomQuery + DeserializeOwned + 'static,
+    {
+        assert_eq!(type_url, "/ibc.applications.transfer.v1.MsgTransfer");
+        Ok(AppResponse {
+            events: vec![Event::new("send_packet").add_attribute("packet_sequence", PACKET_SEQUENCE.to_string())],
+            data: None,
+        })
+    }
+}
+
+type TestApp = App<
+    BankKeeper,
+    MockApiBech32,
+    MockStorage,
+    FailingModule<Empty, Empty, Empty>,
+    WasmKeeper<Empty, Empty>,
+    StakeKeeper,
+    DistributionKeeper,
+    IbcFailingModule,
+    GovFailingModule,
+    IbcTransferStargate,
+>;
+
+/// Bare-bones cw20 that anyone can mint, and which rejects transfers to the
+/// addresses it's instantiated with, so that payouts to them fail.
+mod mock_cw20 {
+    use cosmwasm_schema::cw_serde;
+    use cosmwasm_std::{
+        to_json_binary, Binary, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdError, StdResult, Storage, Uint128,
+    };
+    use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg, Cw20ReceiveMsg, TokenInfoResponse};
+    use cw_multi_test::{Contract, ContractWrapper};
+    use cw_storage_plus::{Item, Map};
+
+    const BALANCES: Map<&str, Uint128> = Map::new("balances");
+    const BLOCKED: Map<&str, ()> = Map::new("blocked");
+    const SUPPLY: Item<Uint128> = Item::new("supply");
+
+    #[cw_serde]
+    pub struct InstantiateMsg {
+        pub balances: Vec<(String, Uint128)>,
+        pub blocked: Vec<String>,
+    }
+
+    pub fn contract() -> Box<dyn Contract<Empty>> {
+        Box::new(ContractWrapper::new_with_empty(execute, instantiate, query))
+    }
+
+    fn instantiate(
+        deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        msg: InstantiateMsg,
+    ) -> StdResult<Response> {
+        SUPPLY.save(deps.storage, &Uint128::zero())?;
+        for (address, amount) in msg.balances.iter() {
+            mint(deps.storage, address, *amount)?;
+        }
+        for address in msg.blocked.iter() {
+            BLOCKED.save(deps.storage, address, &())?;
+        }
+        Ok(Response::new())
+    }
+
+    fn execute(
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        msg: Cw20ExecuteMsg,
+    ) -> StdResult<Response> {
+        match msg {
+            Cw20ExecuteMsg::Transfer { recipient, amount } => {
+                if BLOCKED.has(deps.storage, &recipient) {
+                    return Err(StdError::generic_err("recipient is blocked"));
+                }
+                burn(deps.storage, info.sender.as_str(), amount)?;
+                mint(deps.storage, &recipient, amount)?;
+                Ok(Response::new())
+            },
+            Cw20ExecuteMsg::Send { contract, amount, msg } => {
+                burn(deps.storage, info.sender.as_str(), amount)?;
+                mint(deps.storage, &contract, amount)?;
+                let receive = Cw20ReceiveMsg {
+                    sender: info.sender.to_string(),
+                    amount,
+                    msg,
+                };
+                Ok(Response::new().add_message(receive.into_cosmos_msg(contract)?))
+            },
+            Cw20ExecuteMsg::Mint { recipient, amount } => {
+                mint(deps.storage, &recipient, amount)?;
+                Ok(Response::new())
+            },
+            Cw20ExecuteMsg::Burn { amount } => {
+                burn(deps.storage, info.sender.as_str(), amount)?;
+                Ok(Response::new())
+            },
+            _ => Err(StdError::generic_err("unsupported")),
+        }
+    }
+
+    fn query(
+        deps: Deps,
+        _env: Env,
+        msg: Cw20QueryMsg,
+    ) -> StdResult<Binary> {
+        match msg {
+            Cw20QueryMsg::Balance { address } => to_json_binary(&BalanceResponse {
+                balance: BALANCES.may_load(deps.storage, &address)?.unwrap_or_default(),
+            }),
+            Cw20QueryMsg::TokenInfo {} => to_json_binary(&TokenInfoResponse {
+                name: "Mock".to_owned(),
+                symbol: "MOCK".to_owned(),
+                decimals: 6,
+                total_supply: SUPPLY.load(deps.storage)?,
+            }),
+            _ => Err(StdError::generic_err("unsupported")),
+        }
+    }
+
+    fn mint(
+        store: &mut dyn Storage,
+        address: &str,
+        amount: Uint128,
+    ) -> StdResult<()> {
+        let balance = BALANCES.may_load(store, address)?.unwrap_or_default();
+        BALANCES.save(store, address, &(balance + amount))?;
+        SUPPLY.update(store, |supply| -> StdResult<_> { Ok(supply + amount) })?;
+        Ok(())
+    }
+
+    fn burn(
+        store: &mut dyn Storage,
+        address: &str,
+        amount: Uint128,
+    ) -> StdResult<()> {
+        let balance = BALANCES.may_load(store, address)?.unwrap_or_default();
+        BALANCES.save(store, address, &balance.checked_sub(amount)?)?;
+        SUPPLY.update(store, |supply| -> StdResult<_> { Ok(supply.checked_sub(amount)?) })?;
+        Ok(())
+    }
+}
+
+/// Bare-bones cw721 collection whose tokens are owned by whoever it's
+/// instantiated with.
+mod mock_cw721 {
+    use cosmwasm_schema::cw_serde;
+    use cosmwasm_std::{
+        to_json_binary, Binary, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdError, StdResult, Storage, WasmMsg,
+    };
+    use cw_claims::{cw721::Cw721ReceiveMsg, msg::ExecuteMsg as ClaimsExecuteMsg};
+    use cw_multi_test::{Contract, ContractWrapper};
+    use cw_storage_plus::Map;
+
+    const OWNERS: Map<&str, String> = Map::new("owners");
+
+    #[cw_serde]
+    pub struct InstantiateMsg {
+        pub owners: Vec<(String, String)>,
+    }
+
+    #[cw_serde]
+    pub enum ExecuteMsg {
+        TransferNft {
+            recipient: String,
+            token_id: String,
+        },
+        SendNft {
+            contract: String,
+            token_id: String,
+            msg: Binary,
+        },
+        Burn {
+            token_id: String,
+        },
+    }
+
+    #[cw_serde]
+    pub enum QueryMsg {
+        OwnerOf {
+            token_id: String,
+            include_expired: Option<bool>,
+        },
+    }
+
+    #[cw_serde]
+    pub struct OwnerOfResponse {
+        pub owner: String,
+    }
+
+    pub fn contract() -> Box<dyn Contract<Empty>> {
+        Box::new(ContractWrapper::new_with_empty(execute, instantiate, query))
+    }
+
+    fn instantiate(
+        deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        msg: InstantiateMsg,
+    ) -> StdResult<Response> {
+        for (token_id, owner) in msg.owners.iter() {
+            OWNERS.save(deps.storage, token_id, owner)?;
+        }
+        Ok(Response::new())
+    }
+
+    fn execute(
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        msg: ExecuteMsg,
+    ) -> StdResult<Response> {
+        match msg {
+            ExecuteMsg::TransferNft { recipient, token_id } => {
+                transfer(deps.storage, info.sender.as_str(), &token_id, &recipient)?;
+                Ok(Response::new())
+            },
+            ExecuteMsg::SendNft {
+                contract,
+                token_id,
+                msg,
+            } => {
+                transfer(deps.storage, info.sender.as_str(), &token_id, &contract)?;
+                let receive = ClaimsExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+                    sender: info.sender.to_string(),
+                    token_id,
+                    msg,
+                });
+                Ok(Response::new().add_message(WasmMsg::Execute {
+                    contract_addr: contract,
+                    msg: to_json_binary(&receive)?,
+                    funds: vec![],
+                }))
+            },
+            ExecuteMsg::Burn { token_id } => {
+                ensure_owner(deps.storage, info.sender.as_str(), &token_id)?;
+                OWNERS.remove(deps.storage, &token_id);
+                Ok(Response::new())
+            },
+        }
+    }
+
+    fn query(
+        deps: Deps,
+        _env: Env,
+        msg: QueryMsg,
+    ) -> StdResult<Binary> {
+        match msg {
+            QueryMsg::OwnerOf { token_id, .. } => to_json_binary(&OwnerOfResponse {
+                owner: OWNERS.load(deps.storage, &token_id)?,
+            }),
+        }
+    }
+
+    fn ensure_owner(
+        store: &dyn Storage,
+        sender: &str,
+        token_id: &str,
+    ) -> StdResult<()> {
+        if OWNERS.load(store, token_id)? != sender {
+            return Err(StdError::generic_err("sender does not own the token"));
+        }
+        Ok(())
+    }
+
+    fn transfer(
+        store: &mut dyn Storage,
+        sender: &str,
+        token_id: &str,
+        recipient: &str,
+    ) -> StdResult<()> {
+        ensure_owner(store, sender, token_id)?;
+        OWNERS.save(store, token_id, &recipient.to_owned())
+    }
+}
+
+/// Bare-bones cw1155 contract with balances set on instantiation.
+mod mock_cw1155 {
+    use cosmwasm_schema::cw_serde;
+    use cosmwasm_std::{
+        to_json_binary, Binary, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdError, StdResult, Storage,
+        Uint128, WasmMsg,
+    };
+    use cw_claims::{
+        cw1155::{BalanceResponse, Cw1155BatchReceiveMsg, Cw1155ExecuteMsg, Cw1155QueryMsg},
+        msg::ExecuteMsg as ClaimsExecuteMsg,
+    };
+    use cw_multi_test::{Contract, ContractWrapper};
+    use cw_storage_plus::Map;
+
+    const BALANCES: Map<(&str, &str), Uint128> = Map::new("balances");
+
+    #[cw_serde]
+    pub struct InstantiateMsg {
+        pub balances: Vec<(String, String, Uint128)>,
+    }
+
+    pub fn contract() -> Box<dyn Contract<Empty>> {
+        Box::new(ContractWrapper::new_with_empty(execute, instantiate, query))
+    }
+
+    fn instantiate(
+        deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        msg: InstantiateMsg,
+    ) -> StdResult<Response> {
+        for (owner, token_id, amount) in msg.balances.iter() {
+            mint(deps.storage, owner, token_id, *amount)?;
+        }
+        Ok(Response::new())
+    }
+
+    fn execute(
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        msg: Cw1155ExecuteMsg,
+    ) -> StdResult<Response> {
+        match msg {
+            Cw1155ExecuteMsg::BatchSendFrom { from, to, batch, msg } => {
+                if from != info.sender {
+                    return Err(StdError::generic_err("sender is not the owner"));
+                }
+                for (token_id, amount) in batch.iter() {
+                    burn(deps.storage, &from, token_id, *amount)?;
+                    mint(deps.storage, &to, token_id, *amount)?;
+                }
+                let mut resp = Response::new();
+                if let Some(msg) = msg {
+                    let receive = ClaimsExecuteMsg::BatchReceive(Cw1155BatchReceiveMsg {
+                        operator: info.sender.to_string(),
+                        from: Some(from),
+                        batch,
+                        msg,
+                    });
+                    resp = resp.add_message(WasmMsg::Execute {
+                        contract_addr: to,
+                        msg: to_json_binary(&receive)?,
+                        funds: vec![],
+                    });
+                }
+                Ok(resp)
+            },
+            Cw1155ExecuteMsg::Mint {
+                to, token_id, value, ..
+            } => {
+                mint(deps.storage, &to, &token_id, value)?;
+                Ok(Response::new())
+            },
+            Cw1155ExecuteMsg::BatchBurn { from, batch } => {
+                for (token_id, amount) in batch.iter() {
+                    burn(deps.storage, &from, token_id, *amount)?;
+                }
+                Ok(Response::new())
+            },
+        }
+    }
+
+    fn query(
+        deps: Deps,
+        _env: Env,
+        msg: Cw1155QueryMsg,
+    ) -> StdResult<Binary> {
+        match msg {
+            Cw1155QueryMsg::Balance { owner, token_id } => to_json_binary(&BalanceResponse {
+                balance: BALANCES
+                    .may_load(deps.storage, (&owner, &token_id))?
+                    .unwrap_or_default(),
+            }),
+        }
+    }
+
+    fn mint(
+        store: &mut dyn Storage,
+        owner: &str,
+        token_id: &str,
+        amount: Uint128,
+    ) -> StdResult<()> {
+        let balance = BALANCES.may_load(store, (owner, token_id))?.unwrap_or_default();
+        BALANCES.save(store, (owner, token_id), &(balance + amount))
+    }
+
+    fn burn(
+        store: &mut dyn Storage,
+        owner: &str,
+        token_id: &str,
+        amount: Uint128,
+    ) -> StdResult<()> {
+        let balance = BALANCES.may_load(store, (owner, token_id))?.unwrap_or_default();
+        BALANCES.save(store, (owner, token_id), &balance.checked_sub(amount)?)
+    }
+}
+
+/// Hook and callback receiver that records every message it's sent, or fails
+/// them all.
+mod mock_receiver {
+    use cosmwasm_schema::cw_serde;
+    use cosmwasm_std::{to_json_binary, Binary, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdError, StdResult};
+    use cw_claims::msg::{CallbackExecuteMsg, ClaimPaidMsg, HookExecuteMsg};
+    use cw_multi_test::{Contract, ContractWrapper};
+    use cw_storage_plus::Item;
+    use serde::Deserialize;
+
+    const FAIL: Item<bool> = Item::new("fail");
+    const HOOKS: Item<Vec<HookExecuteMsg>> = Item::new("hooks");
+    const CALLBACKS: Item<Vec<ClaimPaidMsg>> = Item::new("callbacks");
+
+    #[cw_serde]
+    pub struct InstantiateMsg {
+        pub fail: bool,
+    }
+
+    #[derive(Deserialize, Clone, Debug)]
+    #[serde(untagged)]
+    pub enum ExecuteMsg {
+        Hook(HookExecuteMsg),
+        Callback(CallbackExecuteMsg),
+    }
+
+    #[cw_serde]
+    pub enum QueryMsg {
+        Hooks {},
+        Callbacks {},
+    }
+
+    pub fn contract() -> Box<dyn Contract<Empty>> {
+        Box::new(ContractWrapper::new_with_empty(execute, instantiate, query))
+    }
+
+    fn instantiate(
+        deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        msg: InstantiateMsg,
+    ) -> StdResult<Response> {
+        FAIL.save(deps.storage, &msg.fail)?;
+        HOOKS.save(deps.storage, &vec![])?;
+        CALLBACKS.save(deps.storage, &vec![])?;
+        Ok(Response::new())
+    }
+
+    fn execute(
+        deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        msg: ExecuteMsg,
+    ) -> StdResult<Response> {
+        if FAIL.load(deps.storage)? {
+            return Err(StdError::generic_err("receiver failed"));
+        }
+        match msg {
+            ExecuteMsg::Hook(msg) => {
+                let mut msgs = HOOKS.load(deps.storage)?;
+                msgs.push(msg);
+                HOOKS.save(deps.storage, &msgs)?;
+            },
+            ExecuteMsg::Callback(CallbackExecuteMsg::ClaimPaid(msg)) => {
+                let mut msgs = CALLBACKS.load(deps.storage)?;
+                msgs.push(msg);
+                CALLBACKS.save(deps.storage, &msgs)?;
+            },
+        }
+        Ok(Response::new())
+    }
+
+    fn query(
+        deps: Deps,
+        _env: Env,
+        msg: QueryMsg,
+    ) -> StdResult<Binary> {
+        match msg {
+            QueryMsg::Hooks {} => to_json_binary(&HOOKS.load(deps.storage)?),
+            QueryMsg::Callbacks {} => to_json_binary(&CALLBACKS.load(deps.storage)?),
+        }
+    }
+}
+
+struct Suite {
+    app: TestApp,
+    contract: Addr,
+    creator: Addr,
+}
+
+impl Suite {
+    fn new() -> Self {
+        Self::with_config(Config {
+            distributor: None,
+            keeper_fee_pct: None,
+            tokenfactory: None,
+        })
+    }
+
+    fn with_config(config: Config) -> Self {
+        let api = MockApiBech32::new(PREFIX);
+        let creator = api.addr_make("creator");
+        let mut app = AppBuilder::new()
+            .with_api(api)
+            .with_wasm(WasmKeeper::new().with_address_generator(MockAddressGenerator))
+            .with_stargate(IbcTransferStargate)
+            .build(|router, _, storage| {
+                router
+                    .bank
+                    .init_balance(storage, &creator, coins(1_000_000, DENOM))
+                    .unwrap();
+            });
+
+        let code_id = app.store_code(Box::new(
+            ContractWrapper::new_with_empty(contract::execute, contract::instantiate, contract::query)
+                .with_sudo(contract::sudo)
+                .with_reply(contract::reply),
+        ));
+        let contract = app
+            .instantiate_contract(
+                code_id,
+                creator.clone(),
+                &InstantiateMsg { config },
+                &[],
+                "cw-claims",
+                None,
+            )
+            .unwrap();
+
+        let mut suite = Self { app, contract, creator };
+        suite.create_campaign(campaign_msg(CAMPAIGN, vec![TokenSource::Denom(DENOM.to_owned())]));
+        suite
+    }
+
+    fn addr(
+        &self,
+        name: &str,
+    ) -> Addr {
+        self.app.api().addr_make(name)
+    }
+
+    fn execute(
+        &mut self,
+        sender: &Addr,
+        msg: &ExecuteMsg,
+        funds: &[Coin],
+    ) -> AnyResult<AppResponse> {
+        self.app
+            .execute_contract(sender.clone(), self.contract.clone(), msg, funds)
+    }
+
+    fn create_campaign(
+        &mut self,
+        msg: CreateCampaignMsg,
+    ) {
+        let creator = self.creator.clone();
+        self.execute(&creator, &ExecuteMsg::CreateCampaign(msg), &[]).unwrap();
+    }
+
+    fn upsert(
+        &mut self,
+        amounts: Vec<(Addr, Uint128)>,
+    ) -> AppResponse {
+        self.upsert_into(CAMPAIGN, amounts)
+    }
+
+    fn upsert_into(
+        &mut self,
+        name: &str,
+        amounts: Vec<(Addr, Uint128)>,
+    ) -> AppResponse {
+        let total: Uint128 = amounts.iter().map(|x| x.1).sum();
+        let creator = self.creator.clone();
+        self.execute(
+            &creator,
+            &ExecuteMsg::Upsert(upsert_msg(name, Token::Denom(DENOM.to_owned()), amounts)),
+            &coins(total.u128(), DENOM),
+        )
+        .unwrap()
+    }
+
+    fn upsert_shares(
+        &mut self,
+        total: u128,
+        shares: Vec<(Addr, Uint128)>,
+        dust_recipient: DustRecipient,
+    ) {
+        let creator = self.creator.clone();
+        self.execute(
+            &creator,
+            &ExecuteMsg::UpsertShares(UpsertSharesMsg {
+                name: CAMPAIGN.to_owned(),
+                token: Token::Denom(DENOM.to_owned()),
+                total: total.into(),
+                shares,
+                dust_recipient,
+                callback: None,
+            }),
+            &coins(total, DENOM),
+        )
+        .unwrap();
+    }
+
+    fn claim(
+        &mut self,
+        sender: &Addr,
+        msg: ClaimMsg,
+    ) -> AppResponse {
+        self.execute(sender, &ExecuteMsg::Claim(msg), &[]).unwrap()
+    }
+
+    fn distribute(
+        &mut self,
+        sender: &Addr,
+        name: &str,
+        limit: Option<u32>,
+    ) -> AppResponse {
+        self.execute(
+            sender,
+            &ExecuteMsg::Distribute {
+                name: name.to_owned(),
+                limit,
+            },
+            &[],
+        )
+        .unwrap()
+    }
+
+    fn advance_time(
+        &mut self,
+        seconds: u64,
+    ) {
+        self.app.update_block(|block| {
+            block.time = block.time.plus_seconds(seconds);
+            block.height += 1;
+        });
+    }
+
+    fn instantiate_cw20(
+        &mut self,
+        balances: Vec<(&Addr, u128)>,
+        blocked: Vec<&Addr>,
+    ) -> Addr {
+        let code_id = self.app.store_code(mock_cw20::contract());
+        self.app
+            .instantiate_contract(
+                code_id,
+                self.creator.clone(),
+                &mock_cw20::InstantiateMsg {
+                    balances: balances
+                        .into_iter()
+                        .map(|(address, amount)| (address.to_string(), amount.into()))
+                        .collect(),
+                    blocked: blocked.into_iter().map(|address| address.to_string()).collect(),
+                },
+                &[],
+                "cw20",
+                None,
+            )
+            .unwrap()
+    }
+
+    /// Send the creator's cw20 tokens to the contract, executing the message.
+    fn send_cw20(
+        &mut self,
+        cw20: &Addr,
+        amount: u128,
+        msg: &ReceiveMsg,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            self.creator.clone(),
+            cw20.clone(),
+            &Cw20ExecuteMsg::Send {
+                contract: self.contract.to_string(),
+                amount: amount.into(),
+                msg: cosmwasm_std::to_json_binary(msg).unwrap(),
+            },
+            &[],
+        )
+    }
+
+    fn cw20_supply(
+        &self,
+        cw20: &Addr,
+    ) -> u128 {
+        let resp: TokenInfoResponse = self
+            .app
+            .wrap()
+            .query_wasm_smart(cw20, &Cw20QueryMsg::TokenInfo {})
+            .unwrap();
+        resp.total_supply.u128()
+    }
+
+    fn campaign(
+        &self,
+        name: &str,
+    ) -> Campaign {
+        let CampaignResponse(campaign) = self
+            .app
+            .wrap()
+            .query_wasm_smart(&self.contract, &QueryMsg::Campaign { name: name.to_owned() })
+            .unwrap();
+        campaign
+    }
+
+    fn instantiate_cw721(
+        &mut self,
+        owners: Vec<(&str, &Addr)>,
+    ) -> Addr {
+        let code_id = self.app.store_code(mock_cw721::contract());
+        self.app
+            .instantiate_contract(
+                code_id,
+                self.creator.clone(),
+                &mock_cw721::InstantiateMsg {
+                    owners: owners
+                        .into_iter()
+                        .map(|(token_id, owner)| (token_id.to_owned(), owner.to_string()))
+                        .collect(),
+                },
+                &[],
+                "cw721",
+                None,
+            )
+            .unwrap()
+    }
+
+    /// Send the creator's NFT to the contract, executing the message.
+    fn send_nft(
+        &mut self,
+        cw721: &Addr,
+        token_id: &str,
+        msg: &ReceiveMsg,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            self.creator.clone(),
+            cw721.clone(),
+            &mock_cw721::ExecuteMsg::SendNft {
+                contract: self.contract.to_string(),
+                token_id: token_id.to_owned(),
+                msg: cosmwasm_std::to_json_binary(msg).unwrap(),
+            },
+            &[],
+        )
+    }
+
+    fn nft_owner(
+        &self,
+        cw721: &Addr,
+        token_id: &str,
+    ) -> String {
+        let resp: mock_cw721::OwnerOfResponse = self
+            .app
+            .wrap()
+            .query_wasm_smart(
+                cw721,
+                &mock_cw721::QueryMsg::OwnerOf {
+                    token_id: token_id.to_owned(),
+                    include_expired: None,
+                },
+            )
+            .unwrap();
+        resp.owner
+    }
+
+    fn instantiate_cw1155(
+        &mut self,
+        balances: Vec<(&Addr, &str, u128)>,
+    ) -> Addr {
+        let code_id = self.app.store_code(mock_cw1155::contract());
+        self.app
+            .instantiate_contract(
+                code_id,
+                self.creator.clone(),
+                &mock_cw1155::InstantiateMsg {
+                    balances: balances
+                        .into_iter()
+                        .map(|(owner, token_id, amount)| (owner.to_string(), token_id.to_owned(), amount.into()))
+                        .collect(),
+                },
+                &[],
+                "cw1155",
+                None,
+            )
+            .unwrap()
+    }
+
+    /// Send a batch of the creator's cw1155 tokens to the contract, executing
+    /// the message.
+    fn send_cw1155(
+        &mut self,
+        cw1155: &Addr,
+        batch: Vec<(&str, u128)>,
+        msg: &ReceiveMsg,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            self.creator.clone(),
+            cw1155.clone(),
+            &Cw1155ExecuteMsg::BatchSendFrom {
+                from: self.creator.to_string(),
+                to: self.contract.to_string(),
+                batch: batch
+                    .into_iter()
+                    .map(|(token_id, amount)| (token_id.to_owned(), amount.into()))
+                    .collect(),
+                msg: Some(cosmwasm_std::to_json_binary(msg).unwrap()),
+            },
+            &[],
+        )
+    }
+
+    fn cw1155_balance(
+        &self,
+        cw1155: &Addr,
+        owner: &Addr,
+        token_id: &str,
+    ) -> u128 {
+        let resp: cw1155::BalanceResponse = self
+            .app
+            .wrap()
+            .query_wasm_smart(
+                cw1155,
+                &Cw1155QueryMsg::Balance {
+                    owner: owner.to_string(),
+                    token_id: token_id.to_owned(),
+                },
+            )
+            .unwrap();
+        resp.balance.u128()
+    }
+
+    fn instantiate_receiver(
+        &mut self,
+        fail: bool,
+    ) -> Addr {
+        let code_id = self.app.store_code(mock_receiver::contract());
+        self.app
+            .instantiate_contract(
+                code_id,
+                self.creator.clone(),
+                &mock_receiver::InstantiateMsg { fail },
+                &[],
+                "receiver",
+                None,
+            )
+            .unwrap()
+    }
+
+    fn received_hooks(
+        &self,
+        receiver: &Addr,
+    ) -> Vec<HookExecuteMsg> {
+        self.app
+            .wrap()
+            .query_wasm_smart(receiver, &mock_receiver::QueryMsg::Hooks {})
+            .unwrap()
+    }
+
+    fn received_callbacks(
+        &self,
+        receiver: &Addr,
+    ) -> Vec<ClaimPaidMsg> {
+        self.app
+            .wrap()
+            .query_wasm_smart(receiver, &mock_receiver::QueryMsg::Callbacks {})
+            .unwrap()
+    }
+
+    fn claimable(
+        &self,
+        recipient: &Addr,
+    ) -> Option<Uint128> {
+        self.claimable_in(CAMPAIGN, &Token::Denom(DENOM.to_owned()), recipient)
+    }
+
+    fn claimable_in(
+        &self,
+        name: &str,
+        token: &Token,
+        recipient: &Addr,
+    ) -> Option<Uint128> {
+        let ClaimResponse(record) = self
+            .app
+            .wrap()
+            .query_wasm_smart(
+                &self.contract,
+                &QueryMsg::ClaimByKey {
+                    name: name.to_owned(),
+                    token: token.to_owned(),
+                    recipient: recipient.clone(),
+                },
+            )
+            .unwrap();
+        record.map(|r| r.amount)
+    }
+
+    fn pending_totals(
+        &self,
+        address: &Addr,
+    ) -> Vec<PendingTotal> {
+        let resp: ClaimableSummaryResponse = self
+            .app
+            .wrap()
+            .query_wasm_smart(
+                &self.contract,
+                &QueryMsg::ClaimableSummary {
+                    address: address.clone(),
+                },
+            )
+            .unwrap();
+        resp.totals
+    }
+
+    fn campaign_claim_amounts(&self) -> Vec<(Addr, Uint128)> {
+        let resp: CampaignClaimsResponse = self
+            .app
+            .wrap()
+            .query_wasm_smart(
+                &self.contract,
+                &QueryMsg::ClaimsByCampaign(CampaignClaimsQueryMsg {
+                    name: CAMPAIGN.to_owned(),
+                    order_by: CampaignOrderKey::Amount,
+                    cursor: None,
+                }),
+            )
+            .unwrap();
+        resp.claims.into_iter().map(|c| (c.recipient, c.amount)).collect()
+    }
+
+    /// Most recent history entries of the address first.
+    fn history(
+        &self,
+        address: &Addr,
+    ) -> Vec<ClaimHistoryEntry> {
+        let resp: ClaimHistoryResponse = self
+            .app
+            .wrap()
+            .query_wasm_smart(
+                &self.contract,
+                &QueryMsg::ClaimHistory {
+                    scope: ClaimHistoryScope::Address(address.clone()),
+                    cursor: None,
+                },
+            )
+            .unwrap();
+        resp.entries
+    }
+
+    fn claimed_totals(
+        &self,
+        address: &Addr,
+    ) -> Vec<TokenAmount> {
+        let resp: ClaimedTotalsResponse = self
+            .app
+            .wrap()
+            .query_wasm_smart(
+                &self.contract,
+                &QueryMsg::ClaimedTotals {
+                    address: address.clone(),
+                },
+            )
+            .unwrap();
+        resp.totals
+    }
+
+    fn balance(
+        &self,
+        address: &Addr,
+    ) -> u128 {
+        self.app.wrap().query_balance(address, DENOM).unwrap().amount.u128()
+    }
+
+    fn cw20_balance(
+        &self,
+        cw20: &Addr,
+        address: &Addr,
+    ) -> u128 {
+        let resp: BalanceResponse = self
+            .app
+            .wrap()
+            .query_wasm_smart(
+                cw20,
+                &Cw20QueryMsg::Balance {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap();
+        resp.balance.u128()
+    }
+}
+
+fn campaign_msg(
+    name: &str,
+    tokens: Vec<TokenSource>,
+) -> CreateCampaignMsg {
+    CreateCampaignMsg {
+        name: name.to_owned(),
+        title: "Airdrop".to_owned(),
+        description: None,
+        image_url: None,
+        claim_start: None,
+        claim_end: None,
+        tokens,
+        mint: None,
+        expiry_action: None,
+        remote_depositor: None,
+    }
+}
+
+fn upsert_msg(
+    name: &str,
+    token: Token,
+    amounts: Vec<(Addr, Uint128)>,
+) -> UpsertMsg {
+    UpsertMsg {
+        name: name.to_owned(),
+        token,
+        amounts,
+        callback: None,
+        remote_sender: None,
+        foreign_amounts: None,
+    }
+}
+
+fn claim_msg(
+    ids: Option<Vec<Uint64>>,
+    amount: Option<Uint128>,
+) -> ClaimMsg {
+    ClaimMsg {
+        ids,
+        recipient: None,
+        filter: None,
+        cursor: None,
+        amount,
+        ibc: None,
+        proof: None,
+    }
+}
+
+fn upserted_id(resp: &AppResponse) -> Uint64 {
+    let id = resp
+        .events
+        .iter()
+        .filter(|e| e.ty == "wasm-claim_created")
+        .flat_map(|e| e.attributes.iter())
+        .find(|a| a.key == "id")
+        .unwrap()
+        .value
+        .parse::<u64>()
+        .unwrap();
+    id.into()
+}
+
+/// Value of an attribute set on the contract's response.
+fn wasm_attr(
+    resp: &AppResponse,
+    key: &str,
+) -> String {
+    resp.events
+        .iter()
+        .filter(|e| e.ty == "wasm")
+        .flat_map(|e| e.attributes.iter())
+        .find(|a| a.key == key)
+        .unwrap()
+        .value
+        .to_owned()
+}
+
+fn has_event(
+    resp: &AppResponse,
+    ty: &str,
+) -> bool {
+    resp.events.iter().any(|e| e.ty == ty)
+}
+
+#[test]
+fn pro_rata_dust_goes_to_largest_holder() {
+    let mut suite = Suite::new();
+    let (alice, bob, carol) = (suite.addr("alice"), suite.addr("bob"), suite.addr("carol"));
+
+    // 101 split 2:1:2 rounds down to 40 + 20 + 40, leaving 1 of dust for the
+    // first of the largest holders.
+    suite.upsert_shares(
+        101,
+        vec![
+            (alice.clone(), 2u128.into()),
+            (bob.clone(), 1u128.into()),
+            (carol.clone(), 2u128.into()),
+        ],
+        DustRecipient::LargestHolder,
+    );
+
+    assert_eq!(suite.claimable(&alice), Some(41u128.into()));
+    assert_eq!(suite.claimable(&bob), Some(20u128.into()));
+    assert_eq!(suite.claimable(&carol), Some(40u128.into()));
+}
+
+#[test]
+fn pro_rata_dust_goes_to_depositor() {
+    let mut suite = Suite::new();
+    let (alice, bob, dave) = (suite.addr("alice"), suite.addr("bob"), suite.addr("dave"));
+    let creator = suite.creator.clone();
+
+    // Dave's share rounds down to nothing, so he gets no record at all.
+    suite.upsert_shares(
+        10,
+        vec![
+            (alice.clone(), 1_000u128.into()),
+            (bob.clone(), 1_000u128.into()),
+            (dave.clone(), 1u128.into()),
+        ],
+        DustRecipient::Depositor,
+    );
+
+    assert_eq!(suite.claimable(&alice), Some(4u128.into()));
+    assert_eq!(suite.claimable(&bob), Some(4u128.into()));
+    assert_eq!(suite.claimable(&dave), None);
+    assert_eq!(suite.claimable(&creator), Some(2u128.into()));
+}
+
+#[test]
+fn partial_claims_keep_indexes_and_pending_totals_consistent() {
+    let mut suite = Suite::new();
+    let (alice, bob) = (suite.addr("alice"), suite.addr("bob"));
+
+    let id = upserted_id(&suite.upsert(vec![(alice.clone(), 100u128.into())]));
+    suite.upsert(vec![(bob.clone(), 50u128.into())]);
+
+    suite.claim(&alice, claim_msg(Some(vec![id]), Some(70u128.into())));
+
+    assert_eq!(suite.balance(&alice), 70);
+    assert_eq!(suite.claimable(&alice), Some(30u128.into()));
+    assert_eq!(
+        suite.pending_totals(&alice),
+        vec![PendingTotal {
+            token: Token::Denom(DENOM.to_owned()),
+            amount: 30u128.into(),
+            count: 1,
+        }]
+    );
+    // The amount index moves alice's record below bob's
+    assert_eq!(
+        suite.campaign_claim_amounts(),
+        vec![(bob.clone(), 50u128.into()), (alice.clone(), 30u128.into())]
+    );
+
+    // Claiming the remainder removes the record and its index entries
+    suite.claim(&alice, claim_msg(Some(vec![id]), Some(30u128.into())));
+
+    assert_eq!(suite.balance(&alice), 100);
+    assert_eq!(suite.claimable(&alice), None);
+    assert!(suite
+        .pending_totals(&alice)
+        .iter()
+        .all(|t| t.amount.is_zero() && t.count == 0));
+    assert_eq!(suite.campaign_claim_amounts(), vec![(bob, 50u128.into())]);
+}
+
+#[test]
+fn ibc_timeout_recredits_claim() {
+    let mut suite = Suite::new();
+    let alice = suite.addr("alice");
+
+    suite.upsert(vec![(alice.clone(), 100u128.into())]);
+
+    let mut msg = claim_msg(None, None);
+    msg.ibc = Some(IbcDestination {
+        channel_id: CHANNEL.to_owned(),
+        remote_address: "osmo1alice".to_owned(),
+        timeout_seconds: None,
+    });
+    suite.claim(&alice, msg);
+
+    assert_eq!(suite.claimable(&alice), None);
+
+    let contract = suite.contract.clone();
+    suite
+        .app
+        .wasm_sudo(
+            contract,
+            &SudoMsg::IbcLifecycleComplete(IbcLifecycleComplete::IbcTimeout {
+                channel: CHANNEL.to_owned(),
+                sequence: PACKET_SEQUENCE,
+            }),
+        )
+        .unwrap();
+
+    assert_eq!(suite.claimable(&alice), Some(100u128.into()));
+    assert_eq!(
+        suite.pending_totals(&alice),
+        vec![PendingTotal {
+            token: Token::Denom(DENOM.to_owned()),
+            amount: 100u128.into(),
+            count: 1,
+        }]
+    );
+
+    // The restored record can be claimed locally
+    suite.claim(&alice, claim_msg(None, None));
+    assert_eq!(suite.balance(&alice), 100);
+}
+
+#[test]
+fn distribute_pages_through_campaign_with_cursor() {
+    let mut suite = Suite::new();
+    let keeper = suite.addr("keeper");
+    let recipients = [suite.addr("alice"), suite.addr("bob"), suite.addr("carol")];
+
+    suite.upsert(recipients.iter().map(|r| (r.clone(), 10u128.into())).collect());
+
+    let resp = suite.distribute(&keeper, CAMPAIGN, Some(2));
+    assert_eq!(wasm_attr(&resp, "distributed"), "2");
+    assert_eq!(wasm_attr(&resp, "done"), "false");
+    let n_paid = recipients.iter().filter(|r| suite.balance(r) == 10).count();
+    assert_eq!(n_paid, 2);
+
+    // The next call resumes from the cursor rather than starting over
+    let resp = suite.distribute(&keeper, CAMPAIGN, Some(2));
+    assert_eq!(wasm_attr(&resp, "distributed"), "1");
+    assert_eq!(wasm_attr(&resp, "done"), "true");
+    assert!(recipients.iter().all(|r| suite.balance(r) == 10));
+    assert!(recipients.iter().all(|r| suite.claimable(r).is_none()));
+
+    // A fresh pass starts over but has nothing left to pay
+    let resp = suite.distribute(&keeper, CAMPAIGN, Some(2));
+    assert_eq!(wasm_attr(&resp, "distributed"), "0");
+    assert!(recipients.iter().all(|r| suite.balance(r) == 10));
+}
+
+#[test]
+fn distribute_pays_keeper_fee_out_of_claims() {
+    let mut suite = Suite::with_config(Config {
+        distributor: None,
+        keeper_fee_pct: Some(KEEPER_FEE_PCT.into()),
+        tokenfactory: None,
+    });
+    let (alice, keeper) = (suite.addr("alice"), suite.addr("keeper"));
+
+    suite.upsert(vec![(alice.clone(), 100u128.into())]);
+    suite.distribute(&keeper, CAMPAIGN, None);
+
+    assert_eq!(suite.balance(&alice), 95);
+    assert_eq!(suite.balance(&keeper), 5);
+
+    // Only what the recipient received counts as claimed by them
+    let history = suite.history(&alice);
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].amount, Uint128::from(95u128));
+    assert!(!history[0].is_reversal);
+    assert_eq!(suite.claimed_totals(&alice)[0].amount, Uint128::from(95u128));
+}
+
+#[test]
+fn failed_payout_recredits_claim_without_paying_keeper_fee() {
+    let mut suite = Suite::with_config(Config {
+        distributor: None,
+        keeper_fee_pct: Some(KEEPER_FEE_PCT.into()),
+        tokenfactory: None,
+    });
+    let (alice, blocked, keeper) = (suite.addr("alice"), suite.addr("blocked"), suite.addr("keeper"));
+    let creator = suite.creator.clone();
+
+    let cw20 = suite.instantiate_cw20(vec![(&creator, 1_000)], vec![&blocked]);
+    let token = Token::Address(cw20.clone());
+    suite.create_campaign(campaign_msg("tokens", vec![TokenSource::Contract(cw20.clone())]));
+    suite
+        .send_cw20(
+            &cw20,
+            200,
+            &ReceiveMsg::Upsert(upsert_msg(
+                "tokens",
+                token.clone(),
+                vec![(alice.clone(), 100u128.into()), (blocked.clone(), 100u128.into())],
+            )),
+        )
+        .unwrap();
+
+    let resp = suite.distribute(&keeper, "tokens", None);
+    assert!(has_event(&resp, "wasm-claim_restored"));
+
+    // The keeper is only paid its fee on the claim that went through
+    assert_eq!(suite.cw20_balance(&cw20, &alice), 95);
+    assert_eq!(suite.cw20_balance(&cw20, &keeper), 5);
+    assert_eq!(suite.cw20_balance(&cw20, &suite.contract), 100);
+
+    // The failed claim is re-credited in full, fee included, and its receipt
+    // is reversed.
+    assert_eq!(suite.claimable_in("tokens", &token, &blocked), Some(100u128.into()));
+    let history = suite.history(&blocked);
+    assert_eq!(history.len(), 2);
+    assert!(history[0].is_reversal);
+    assert_eq!(history[0].amount, history[1].amount);
+    assert!(suite.claimed_totals(&blocked).iter().all(|t| t.amount.is_zero()));
+}
+
+#[test]
+fn failed_keeper_fee_payout_is_recredited_to_keeper() {
+    let mut suite = Suite::with_config(Config {
+        distributor: None,
+        keeper_fee_pct: Some(KEEPER_FEE_PCT.into()),
+        tokenfactory: None,
+    });
+    let (alice, blocked) = (suite.addr("alice"), suite.addr("blocked"));
+    let creator = suite.creator.clone();
+
+    let cw20 = suite.instantiate_cw20(vec![(&creator, 1_000)], vec![&blocked]);
+    let token = Token::Address(cw20.clone());
+    suite.create_campaign(campaign_msg("tokens", vec![TokenSource::Contract(cw20.clone())]));
+    suite
+        .send_cw20(
+            &cw20,
+            100,
+            &ReceiveMsg::Upsert(upsert_msg(
+                "tokens",
+                token.clone(),
+                vec![(alice.clone(), 100u128.into())],
+            )),
+        )
+        .unwrap();
+
+    suite.distribute(&blocked, "tokens", None);
+
+    assert_eq!(suite.cw20_balance(&cw20, &alice), 95);
+    assert_eq!(suite.claimable_in("tokens", &token, &blocked), Some(5u128.into()));
+}
+
+/// Create a campaign whose claim window ends after `seconds`.
+fn create_expiring_campaign(
+    suite: &mut Suite,
+    name: &str,
+    tokens: Vec<TokenSource>,
+    seconds: u64,
+) {
+    let mut msg = campaign_msg(name, tokens);
+    msg.claim_end = Some(suite.app.block_info().time.plus_seconds(seconds));
+    suite.create_campaign(msg);
+}
+
+#[test]
+fn recover_pages_unclaimed_records_back_to_creator() {
+    let mut suite = Suite::new();
+    let (alice, bob, carol) = (suite.addr("alice"), suite.addr("bob"), suite.addr("carol"));
+    let creator = suite.creator.clone();
+    let initial_balance = suite.balance(&creator);
+
+    create_expiring_campaign(&mut suite, "expiring", vec![TokenSource::Denom(DENOM.to_owned())], 100);
+    suite.upsert_into(
+        "expiring",
+        vec![
+            (alice.clone(), 10u128.into()),
+            (bob.clone(), 10u128.into()),
+            (carol.clone(), 10u128.into()),
+        ],
+    );
+    suite.claim(&alice, claim_msg(None, None));
+
+    let recover = ExecuteMsg::Recover {
+        name: "expiring".to_owned(),
+        limit: Some(1),
+    };
+    let err = suite.execute(&creator, &recover, &[]).unwrap_err();
+    assert!(err.root_cause().to_string().contains("has not reached the end"));
+
+    suite.advance_time(100);
+
+    // Nothing is paid out once the window has ended
+    let _ = suite.execute(&bob, &ExecuteMsg::Claim(claim_msg(None, None)), &[]);
+    assert_eq!(suite.balance(&bob), 0);
+    // Only the creator can recover
+    assert!(suite.execute(&bob, &recover, &[]).is_err());
+
+    let mut n_recovered = 0;
+    let mut n_calls = 0;
+    loop {
+        let resp = suite.execute(&creator, &recover, &[]).unwrap();
+        n_recovered += wasm_attr(&resp, "recovered").parse::<usize>().unwrap();
+        n_calls += 1;
+        if wasm_attr(&resp, "done") == "true" {
+            break;
+        }
+    }
+
+    assert!(n_calls > 1);
+    assert_eq!(n_recovered, 2);
+    assert_eq!(suite.balance(&creator), initial_balance - 10);
+    assert_eq!(
+        suite.claimable_in("expiring", &Token::Denom(DENOM.to_owned()), &bob),
+        None
+    );
+    assert_eq!(suite.campaign("expiring").status, CampaignStatus::Finalized);
+}
+
+#[test]
+fn failed_refund_is_recredited_to_creator() {
+    let mut suite = Suite::new();
+    let alice = suite.addr("alice");
+    let creator = suite.creator.clone();
+
+    let cw20 = suite.instantiate_cw20(vec![(&creator, 1_000)], vec![&creator]);
+    let token = Token::Address(cw20.clone());
+    create_expiring_campaign(&mut suite, "expiring", vec![TokenSource::Contract(cw20.clone())], 100);
+    suite
+        .send_cw20(
+            &cw20,
+            100,
+            &ReceiveMsg::Upsert(upsert_msg(
+                "expiring",
+                token.clone(),
+                vec![(alice.clone(), 100u128.into())],
+            )),
+        )
+        .unwrap();
+
+    suite.advance_time(100);
+    let resp = suite
+        .execute(
+            &creator,
+            &ExecuteMsg::Recover {
+                name: "expiring".to_owned(),
+                limit: None,
+            },
+            &[],
+        )
+        .unwrap();
+
+    assert!(has_event(&resp, "wasm-claim_restored"));
+    assert_eq!(suite.cw20_balance(&cw20, &creator), 900);
+    assert_eq!(suite.cw20_balance(&cw20, &suite.contract), 100);
+    assert_eq!(suite.claimable_in("expiring", &token, &alice), None);
+    assert_eq!(suite.claimable_in("expiring", &token, &creator), Some(100u128.into()));
+    assert_ne!(suite.campaign("expiring").status, CampaignStatus::Finalized);
+}
+
+#[test]
+fn finalize_expired_sends_unclaimed_funds_to_address() {
+    let mut suite = Suite::new();
+    let (alice, bob, treasury, keeper) = (
+        suite.addr("alice"),
+        suite.addr("bob"),
+        suite.addr("treasury"),
+        suite.addr("keeper"),
+    );
+
+    let mut msg = campaign_msg("expiring", vec![TokenSource::Denom(DENOM.to_owned())]);
+    msg.claim_end = Some(suite.app.block_info().time.plus_seconds(100));
+    msg.expiry_action = Some(ExpiryAction::SendTo(treasury.clone()));
+    suite.create_campaign(msg);
+    suite.upsert_into(
+        "expiring",
+        vec![(alice.clone(), 10u128.into()), (bob.clone(), 20u128.into())],
+    );
+    suite.claim(&alice, claim_msg(None, None));
+
+    let finalize = ExecuteMsg::FinalizeExpired {
+        name: "expiring".to_owned(),
+        limit: None,
+    };
+    assert!(suite.execute(&keeper, &finalize, &[]).is_err());
+
+    // Anyone can finalize once the window has ended
+    suite.advance_time(100);
+    let resp = suite.execute(&keeper, &finalize, &[]).unwrap();
+
+    assert_eq!(wasm_attr(&resp, "recovered"), "1");
+    assert_eq!(wasm_attr(&resp, "done"), "true");
+    assert_eq!(suite.balance(&treasury), 20);
+    assert_eq!(suite.balance(&bob), 0);
+    assert_eq!(suite.campaign("expiring").status, CampaignStatus::Finalized);
+}
+
+#[test]
+fn finalize_expired_burns_unclaimed_cw20() {
+    let mut suite = Suite::new();
+    let (alice, bob) = (suite.addr("alice"), suite.addr("bob"));
+    let creator = suite.creator.clone();
+
+    let cw20 = suite.instantiate_cw20(vec![(&creator, 1_000)], vec![]);
+    let token = Token::Address(cw20.clone());
+    let mut msg = campaign_msg("expiring", vec![TokenSource::Contract(cw20.clone())]);
+    msg.claim_end = Some(suite.app.block_info().time.plus_seconds(100));
+    msg.expiry_action = Some(ExpiryAction::Burn);
+    suite.create_campaign(msg);
+    suite
+        .send_cw20(
+            &cw20,
+            30,
+            &ReceiveMsg::Upsert(upsert_msg(
+                "expiring",
+                token,
+                vec![(alice.clone(), 10u128.into()), (bob.clone(), 20u128.into())],
+            )),
+        )
+        .unwrap();
+    suite.claim(&alice, claim_msg(None, None));
+
+    suite.advance_time(100);
+    let resp = suite
+        .execute(
+            &bob,
+            &ExecuteMsg::FinalizeExpired {
+                name: "expiring".to_owned(),
+                limit: None,
+            },
+            &[],
+        )
+        .unwrap();
+
+    assert!(has_event(&resp, "wasm-tokens_burned"));
+    assert_eq!(suite.cw20_balance(&cw20, &suite.contract), 0);
+    assert_eq!(suite.cw20_supply(&cw20), 980);
+    assert_eq!(suite.cw20_balance(&cw20, &creator), 970);
+}
+
+#[test]
+fn burn_on_expiry_rejects_unburnable_denoms() {
+    let mut suite = Suite::new();
+    let creator = suite.creator.clone();
+
+    // Without the tokenfactory module, the contract can't burn native denoms
+    let mut msg = campaign_msg("expiring", vec![TokenSource::Denom(DENOM.to_owned())]);
+    msg.expiry_action = Some(ExpiryAction::Burn);
+    let err = suite
+        .execute(&creator, &ExecuteMsg::CreateCampaign(msg), &[])
+        .unwrap_err();
+    assert!(err.root_cause().to_string().contains("cannot be burned"));
+}
+
+/// Create a campaign minting the given cw20 on claim.
+fn create_minted_campaign(
+    suite: &mut Suite,
+    name: &str,
+    cw20: &Addr,
+    cap: u128,
+) {
+    let mut msg = campaign_msg(name, vec![]);
+    msg.mint = Some(MintSettings {
+        token: Token::Address(cw20.clone()),
+        cap: cap.into(),
+    });
+    suite.create_campaign(msg);
+}
+
+#[test]
+fn minted_upserts_are_capped_and_minted_on_claim() {
+    let mut suite = Suite::new();
+    let (alice, bob, carol) = (suite.addr("alice"), suite.addr("bob"), suite.addr("carol"));
+    let creator = suite.creator.clone();
+
+    let cw20 = suite.instantiate_cw20(vec![], vec![]);
+    let token = Token::Address(cw20.clone());
+    create_minted_campaign(&mut suite, "minted", &cw20, 100);
+
+    suite
+        .execute(
+            &creator,
+            &ExecuteMsg::Upsert(upsert_msg(
+                "minted",
+                token.clone(),
+                vec![(alice.clone(), 60u128.into()), (bob.clone(), 40u128.into())],
+            )),
+            &[],
+        )
+        .unwrap();
+
+    // Nothing is minted until it's claimed
+    assert_eq!(suite.cw20_supply(&cw20), 0);
+
+    let err = suite
+        .execute(
+            &creator,
+            &ExecuteMsg::Upsert(upsert_msg("minted", token.clone(), vec![(carol.clone(), 1u128.into())])),
+            &[],
+        )
+        .unwrap_err();
+    assert!(err.root_cause().to_string().contains("exceeds mint cap"));
+
+    suite.claim(&alice, claim_msg(None, None));
+
+    assert_eq!(suite.cw20_balance(&cw20, &alice), 60);
+    assert_eq!(suite.cw20_supply(&cw20), 60);
+    assert_eq!(suite.cw20_balance(&cw20, &suite.contract), 0);
+}
+
+#[test]
+fn returned_minted_payouts_are_reused_instead_of_minted_again() {
+    let mut suite = Suite::new();
+    let (blocked, wallet, keeper) = (suite.addr("blocked"), suite.addr("wallet"), suite.addr("keeper"));
+    let creator = suite.creator.clone();
+
+    let cw20 = suite.instantiate_cw20(vec![], vec![&blocked]);
+    let token = Token::Address(cw20.clone());
+    create_minted_campaign(&mut suite, "minted", &cw20, 100);
+    suite
+        .execute(
+            &creator,
+            &ExecuteMsg::Upsert(upsert_msg(
+                "minted",
+                token.clone(),
+                vec![(blocked.clone(), 100u128.into())],
+            )),
+            &[],
+        )
+        .unwrap();
+
+    // The payout is minted but can't be delivered, so it's held for the claim
+    suite.distribute(&keeper, "minted", None);
+
+    assert_eq!(suite.claimable_in("minted", &token, &blocked), Some(100u128.into()));
+    assert_eq!(suite.cw20_supply(&cw20), 100);
+    assert_eq!(suite.cw20_balance(&cw20, &suite.contract), 100);
+
+    let mut msg = claim_msg(None, None);
+    msg.recipient = Some(wallet.clone());
+    suite.claim(&blocked, msg);
+
+    assert_eq!(suite.cw20_balance(&cw20, &wallet), 100);
+    assert_eq!(suite.cw20_supply(&cw20), 100);
+    assert_eq!(suite.cw20_balance(&cw20, &suite.contract), 0);
+}
+
+#[test]
+fn nft_claims_transfer_the_token() {
+    let mut suite = Suite::new();
+    let alice = suite.addr("alice");
+    let creator = suite.creator.clone();
+
+    let cw721 = suite.instantiate_cw721(vec![("punk-1", &creator)]);
+    let nft = Token::Nft {
+        collection: cw721.clone(),
+        token_id: "punk-1".to_owned(),
+    };
+    suite.create_campaign(campaign_msg("nfts", vec![TokenSource::Contract(cw721.clone())]));
+
+    // The upserted token must be the one received
+    let other = Token::Nft {
+        collection: cw721.clone(),
+        token_id: "punk-2".to_owned(),
+    };
+    let err = suite
+        .send_nft(
+            &cw721,
+            "punk-1",
+            &ReceiveMsg::Upsert(upsert_msg("nfts", other, vec![(alice.clone(), 1u128.into())])),
+        )
+        .unwrap_err();
+    assert!(err.root_cause().to_string().contains("does not match"));
+
+    suite
+        .send_nft(
+            &cw721,
+            "punk-1",
+            &ReceiveMsg::Upsert(upsert_msg("nfts", nft.clone(), vec![(alice.clone(), 1u128.into())])),
+        )
+        .unwrap();
+    assert_eq!(suite.nft_owner(&cw721, "punk-1"), suite.contract.to_string());
+    assert_eq!(suite.claimable_in("nfts", &nft, &alice), Some(1u128.into()));
+
+    suite.claim(&alice, claim_msg(None, None));
+
+    assert_eq!(suite.nft_owner(&cw721, "punk-1"), alice.to_string());
+    assert_eq!(suite.claimable_in("nfts", &nft, &alice), None);
+}
+
+#[test]
+fn nft_upserts_cannot_duplicate_a_pending_claim() {
+    let mut suite = Suite::new();
+    let alice = suite.addr("alice");
+    let creator = suite.creator.clone();
+
+    let cw721 = suite.instantiate_cw721(vec![("punk-1", &creator)]);
+    let nft = Token::Nft {
+        collection: cw721.clone(),
+        token_id: "punk-1".to_owned(),
+    };
+    suite.create_campaign(campaign_msg("nfts", vec![TokenSource::Contract(cw721.clone())]));
+
+    let upsert = ReceiveMsg::Upsert(upsert_msg("nfts", nft.clone(), vec![(alice.clone(), 1u128.into())]));
+    suite.send_nft(&cw721, "punk-1", &upsert).unwrap();
+
+    // A collection reporting the same token again can't take the claim to 2
+    let err = suite
+        .execute(
+            &cw721,
+            &ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+                sender: creator.to_string(),
+                token_id: "punk-1".to_owned(),
+                msg: cosmwasm_std::to_json_binary(&upsert).unwrap(),
+            }),
+            &[],
+        )
+        .unwrap_err();
+    assert!(err.root_cause().to_string().contains("already has a pending claim"));
+    assert_eq!(suite.claimable_in("nfts", &nft, &alice), Some(1u128.into()));
+}
+
+fn multi_token(
+    contract: &Addr,
+    token_id: &str,
+) -> Token {
+    Token::MultiToken {
+        contract: contract.clone(),
+        token_id: token_id.to_owned(),
+    }
+}
+
+#[test]
+fn batched_multi_tokens_are_claimed_in_one_transfer() {
+    let mut suite = Suite::new();
+    let (alice, bob) = (suite.addr("alice"), suite.addr("bob"));
+    let creator = suite.creator.clone();
+
+    let cw1155 = suite.instantiate_cw1155(vec![(&creator, "gold", 100), (&creator, "silver", 50)]);
+    suite.create_campaign(campaign_msg("items", vec![TokenSource::Contract(cw1155.clone())]));
+    suite
+        .send_cw1155(
+            &cw1155,
+            vec![("gold", 100), ("silver", 50)],
+            &ReceiveMsg::UpsertBatch(vec![
+                upsert_msg(
+                    "items",
+                    multi_token(&cw1155, "gold"),
+                    vec![(alice.clone(), 60u128.into()), (bob.clone(), 40u128.into())],
+                ),
+                upsert_msg(
+                    "items",
+                    multi_token(&cw1155, "silver"),
+                    vec![(alice.clone(), 50u128.into())],
+                ),
+            ]),
+        )
+        .unwrap();
+
+    let resp = suite.claim(&alice, claim_msg(None, None));
+
+    assert_eq!(suite.cw1155_balance(&cw1155, &alice, "gold"), 60);
+    assert_eq!(suite.cw1155_balance(&cw1155, &alice, "silver"), 50);
+    assert_eq!(suite.cw1155_balance(&cw1155, &suite.contract, "gold"), 40);
+    assert_eq!(
+        suite.claimable_in("items", &multi_token(&cw1155, "gold"), &bob),
+        Some(40u128.into())
+    );
+
+    // Both token IDs are sent in a single BatchSendFrom
+    let n_transfers = resp
+        .events
+        .iter()
+        .filter(|e| e.ty == "execute" && e.attributes.iter().any(|a| a.value == cw1155.as_str()))
+        .count();
+    assert_eq!(n_transfers, 1);
+}
+
+#[test]
+fn multi_token_upserts_must_match_the_received_batch() {
+    let mut suite = Suite::new();
+    let alice = suite.addr("alice");
+    let creator = suite.creator.clone();
+
+    let cw1155 = suite.instantiate_cw1155(vec![(&creator, "gold", 100)]);
+    suite.create_campaign(campaign_msg("items", vec![TokenSource::Contract(cw1155.clone())]));
+
+    let err = suite
+        .send_cw1155(
+            &cw1155,
+            vec![("gold", 100)],
+            &ReceiveMsg::Upsert(upsert_msg(
+                "items",
+                multi_token(&cw1155, "gold"),
+                vec![(alice.clone(), 90u128.into())],
+            )),
+        )
+        .unwrap_err();
+
+    assert!(err.root_cause().to_string().contains("do not match"));
+    assert_eq!(suite.cw1155_balance(&cw1155, &creator, "gold"), 100);
+}
+
+/// Whether any response attribute set by the contract has the given value.
+fn has_action(
+    resp: &AppResponse,
+    action: &str,
+) -> bool {
+    resp.events
+        .iter()
+        .filter(|e| e.ty == "wasm")
+        .flat_map(|e| e.attributes.iter())
+        .any(|a| a.key == "action" && a.value == action)
+}
+
+#[test]
+fn hooks_are_notified_without_failures_blocking_claims() {
+    let mut suite = Suite::new();
+    let alice = suite.addr("alice");
+    let creator = suite.creator.clone();
+
+    let good = suite.instantiate_receiver(false);
+    let bad = suite.instantiate_receiver(true);
+
+    // Only the owner manages hooks
+    let add_good = ExecuteMsg::AddHook {
+        address: good.clone(),
+        name: None,
+    };
+    assert!(suite.execute(&alice, &add_good, &[]).is_err());
+    suite.execute(&creator, &add_good, &[]).unwrap();
+    suite
+        .execute(
+            &creator,
+            &ExecuteMsg::AddHook {
+                address: bad.clone(),
+                name: Some(CAMPAIGN.to_owned()),
+            },
+            &[],
+        )
+        .unwrap();
+
+    let resp = suite.upsert(vec![(alice.clone(), 10u128.into())]);
+    assert!(has_action(&resp, "hook_failed"));
+
+    let resp = suite.claim(&alice, claim_msg(None, None));
+    assert!(has_action(&resp, "hook_failed"));
+    assert_eq!(suite.balance(&alice), 10);
+
+    let hooks = suite.received_hooks(&good);
+    assert_eq!(hooks.len(), 2);
+    match &hooks[0] {
+        HookExecuteMsg::UpsertHook(msg) => {
+            assert_eq!(msg.depositor, creator);
+            assert_eq!(msg.claims.len(), 1);
+            assert_eq!(msg.claims[0].delta, Uint128::from(10u128));
+        },
+        msg => panic!("expected an upsert hook, got {:?}", msg),
+    }
+    match &hooks[1] {
+        HookExecuteMsg::ClaimHook(msg) => {
+            assert_eq!(msg.claims.len(), 1);
+            assert_eq!(msg.claims[0].claim.recipient, alice);
+            assert_eq!(msg.claims[0].delta, Uint128::from(10u128));
+        },
+        msg => panic!("expected a claim hook, got {:?}", msg),
+    }
+}
+
+/// Upsert native funds into the default campaign, registering its callback.
+fn upsert_with_callback(
+    suite: &mut Suite,
+    callback: &Addr,
+    amounts: Vec<(Addr, Uint128)>,
+) {
+    let total: Uint128 = amounts.iter().map(|x| x.1).sum();
+    let mut msg = upsert_msg(CAMPAIGN, Token::Denom(DENOM.to_owned()), amounts);
+    msg.callback = Some(callback.clone());
+    let creator = suite.creator.clone();
+    suite
+        .execute(&creator, &ExecuteMsg::Upsert(msg), &coins(total.u128(), DENOM))
+        .unwrap();
+}
+
+#[test]
+fn callback_is_sent_once_per_campaign_with_every_payout() {
+    let mut suite = Suite::new();
+    let (alice, bob, keeper) = (suite.addr("alice"), suite.addr("bob"), suite.addr("keeper"));
+
+    let callback = suite.instantiate_receiver(false);
+    upsert_with_callback(
+        &mut suite,
+        &callback,
+        vec![(alice.clone(), 10u128.into()), (bob.clone(), 20u128.into())],
+    );
+
+    suite.distribute(&keeper, CAMPAIGN, None);
+
+    let callbacks = suite.received_callbacks(&callback);
+    assert_eq!(callbacks.len(), 1);
+    assert_eq!(callbacks[0].name, CAMPAIGN);
+    let mut payouts: Vec<(Addr, Uint128)> = callbacks[0]
+        .payouts
+        .iter()
+        .map(|p| (p.recipient.clone(), p.amount))
+        .collect();
+    payouts.sort();
+    let mut expected = vec![(alice, Uint128::from(10u128)), (bob, Uint128::from(20u128))];
+    expected.sort();
+    assert_eq!(payouts, expected);
+}
+
+#[test]
+fn failing_callback_does_not_block_claims() {
+    let mut suite = Suite::new();
+    let alice = suite.addr("alice");
+
+    let callback = suite.instantiate_receiver(true);
+    upsert_with_callback(&mut suite, &callback, vec![(alice.clone(), 10u128.into())]);
+
+    let resp = suite.claim(&alice, claim_msg(None, None));
+
+    assert!(has_action(&resp, "callback_failed"));
+    assert_eq!(suite.balance(&alice), 10);
+    assert_eq!(suite.claimable(&alice), None);
+}
+
+/// Sign over the contract and claimant addresses, as a key proof requires.
+fn key_proof(
+    key: &SigningKey,
+    contract: &Addr,
+    claimant: &Addr,
+) -> KeyProof {
+    let preimage = [contract.as_bytes(), claimant.as_bytes()].concat();
+    KeyProof {
+        pubkey: Binary::from(key.public_key().to_bytes()),
+        signature: Binary::from(key.sign(&preimage).unwrap().to_vec()),
+    }
+}
+
+#[test]
+fn public_key_recipients_claim_with_a_key_proof() {
+    let mut suite = Suite::new();
+    let (claimant, mallory) = (suite.addr("claimant"), suite.addr("mallory"));
+    let creator = suite.creator.clone();
+    let key = SigningKey::from_slice(&[7u8; 32]).unwrap();
+    let pubkey = Binary::from(key.public_key().to_bytes());
+
+    let mut msg = upsert_msg(CAMPAIGN, Token::Denom(DENOM.to_owned()), vec![]);
+    msg.foreign_amounts = Some(vec![(ForeignRecipient::PubKey(pubkey), 100u128.into())]);
+    suite
+        .execute(&creator, &ExecuteMsg::Upsert(msg), &coins(100, DENOM))
+        .unwrap();
+
+    // A proof signed for another claimant can't be replayed
+    let mut msg = claim_msg(None, None);
+    msg.proof = Some(key_proof(&key, &suite.contract, &claimant));
+    let err = suite
+        .execute(&mallory, &ExecuteMsg::Claim(msg.clone()), &[])
+        .unwrap_err();
+    assert!(err.root_cause().to_string().contains("invalid public key signature"));
+
+    suite.claim(&claimant, msg);
+
+    assert_eq!(suite.balance(&claimant), 100);
+    assert_eq!(suite.balance(&mallory), 0);
+}
+
+#[test]
+fn foreign_bech32_recipients_are_credited_to_the_local_address() {
+    let mut suite = Suite::new();
+    let alice = suite.addr("alice");
+    let creator = suite.creator.clone();
+
+    // Same account bytes as alice, under another chain's prefix
+    let (_, data, variant) = bech32::decode(alice.as_str()).unwrap();
+    let remote = bech32::encode("osmo", data, variant).unwrap();
+
+    let mut msg = upsert_msg(CAMPAIGN, Token::Denom(DENOM.to_owned()), vec![]);
+    msg.foreign_amounts = Some(vec![(ForeignRecipient::Bech32(remote), 100u128.into())]);
+    suite
+        .execute(&creator, &ExecuteMsg::Upsert(msg), &coins(100, DENOM))
+        .unwrap();
+
+    assert_eq!(suite.claimable(&alice), Some(100u128.into()));
+}
+
+/// Local sender that IBC hooks execute a remote sender's messages as.
+fn intermediate_sender(remote: &RemoteSender) -> Addr {
+    use bech32::{ToBase32, Variant};
+    use sha2::{Digest, Sha256};
+
+    let hash = Sha256::new()
+        .chain_update(Sha256::digest(b"ibc-wasm-hook-intermediary"))
+        .chain_update(format!("{}/{}", remote.channel_id, remote.address).as_bytes())
+        .finalize();
+    Addr::unchecked(bech32::encode(PREFIX, hash.to_base32(), Variant::Bech32).unwrap())
+}
+
+#[test]
+fn only_the_registered_remote_depositor_can_upsert_and_recover() {
+    let mut suite = Suite::new();
+    let alice = suite.addr("alice");
+    let creator = suite.creator.clone();
+
+    let remote = RemoteSender {
+        channel_id: CHANNEL.to_owned(),
+        address: "osmo1depositor".to_owned(),
+    };
+    let impostor = RemoteSender {
+        channel_id: CHANNEL.to_owned(),
+        address: "osmo1impostor".to_owned(),
+    };
+    let (sender, impostor_sender) = (intermediate_sender(&remote), intermediate_sender(&impostor));
+    for address in [&sender, &impostor_sender] {
+        suite
+            .app
+            .send_tokens(creator.clone(), address.clone(), &coins(100, DENOM))
+            .unwrap();
+    }
+
+    let mut msg = campaign_msg("remote", vec![TokenSource::Denom(DENOM.to_owned())]);
+    msg.claim_end = Some(suite.app.block_info().time.plus_seconds(100));
+    msg.remote_depositor = Some(remote.clone());
+    suite.create_campaign(msg);
+
+    let upsert = |remote_sender: &RemoteSender| {
+        let mut msg = upsert_msg(
+            "remote",
+            Token::Denom(DENOM.to_owned()),
+            vec![(alice.clone(), 100u128.into())],
+        );
+        msg.remote_sender = Some(remote_sender.clone());
+        ExecuteMsg::Upsert(msg)
+    };
+
+    // Any remote sender can reach the contract, but only the registered one
+    // can act on the campaign.
+    let err = suite
+        .execute(&impostor_sender, &upsert(&impostor), &coins(100, DENOM))
+        .unwrap_err();
+    assert!(err
+        .root_cause()
+        .to_string()
+        .contains("not the campaign's remote depositor"));
+
+    // Nor can a local sender claim to be the remote depositor
+    let err = suite
+        .execute(&creator, &upsert(&remote), &coins(100, DENOM))
+        .unwrap_err();
+    assert!(err.root_cause().to_string().contains("does not correspond"));
+
+    suite.execute(&sender, &upsert(&remote), &coins(100, DENOM)).unwrap();
+    assert_eq!(
+        suite.claimable_in("remote", &Token::Denom(DENOM.to_owned()), &alice),
+        Some(100u128.into())
+    );
+
+    suite.advance_time(100);
+    let recover = ExecuteMsg::Recover {
+        name: "remote".to_owned(),
+        limit: None,
+    };
+    assert!(suite.execute(&impostor_sender, &recover, &[]).is_err());
+
+    // Recovered funds go back to the remote depositor over IBC
+    let resp = suite.execute(&sender, &recover, &[]).unwrap();
+    assert_eq!(wasm_attr(&resp, "recovered"), "1");
+    assert!(has_event(&resp, "send_packet"));
+    assert_eq!(
+        suite.claimable_in("remote", &Token::Denom(DENOM.to_owned()), &alice),
+        None
+    );
+    assert_eq!(suite.balance(&sender), 0);
+}
+
+/// Claim everything over IBC, then report the packet's acknowledgement.
+fn claim_over_ibc_and_ack(
+    suite: &mut Suite,
+    claimant: &Addr,
+    success: bool,
+) {
+    let mut msg = claim_msg(None, None);
+    msg.ibc = Some(IbcDestination {
+        channel_id: CHANNEL.to_owned(),
+        remote_address: "osmo1alice".to_owned(),
+        timeout_seconds: None,
+    });
+    suite.claim(claimant, msg);
+
+    let contract = suite.contract.clone();
+    suite
+        .app
+        .wasm_sudo(
+            contract,
+            &SudoMsg::IbcLifecycleComplete(IbcLifecycleComplete::IbcAck {
+                channel: CHANNEL.to_owned(),
+                sequence: PACKET_SEQUENCE,
+                ack: String::new(),
+                success,
+            }),
+        )
+        .unwrap();
+}
+
+#[test]
+fn ibc_error_ack_recredits_claim_and_reverses_history() {
+    let mut suite = Suite::new();
+    let alice = suite.addr("alice");
+
+    // A successful ack settles the transfer
+    suite.upsert(vec![(alice.clone(), 100u128.into())]);
+    claim_over_ibc_and_ack(&mut suite, &alice, true);
+
+    assert_eq!(suite.claimable(&alice), None);
+    assert_eq!(suite.claimed_totals(&alice)[0].amount, Uint128::from(100u128));
+
+    // An error ack returns the funds to the claimant's records
+    suite.upsert(vec![(alice.clone(), 50u128.into())]);
+    claim_over_ibc_and_ack(&mut suite, &alice, false);
+
+    assert_eq!(suite.claimable(&alice), Some(50u128.into()));
+    assert_eq!(suite.claimed_totals(&alice)[0].amount, Uint128::from(100u128));
+    let history = suite.history(&alice);
+    assert!(history[0].is_reversal);
+    assert_eq!(history[0].amount, Uint128::from(50u128));
+}